@@ -0,0 +1,47 @@
+use std::fmt::{Debug, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+// Every width can be widened to u128 losslessly, which is what the statistics work with
+pub trait Calories:
+    Copy + Ord + Default + Debug + Display + FromStr<Err = ParseIntError> + Into<u128>
+{
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_calories {
+    ($($calories_type:ty),*) => {
+        $(
+            impl Calories for $calories_type {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$calories_type>::checked_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_calories!(u32, u64, u128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalorieWidth {
+    U32,
+    U64,
+    U128,
+}
+
+impl FromStr for CalorieWidth {
+    type Err = String;
+
+    fn from_str(width: &str) -> Result<Self, Self::Err> {
+        match width.to_lowercase().as_str() {
+            "u32" | "32" => Ok(Self::U32),
+            "u64" | "64" => Ok(Self::U64),
+            "u128" | "128" => Ok(Self::U128),
+            _ => Err(format!(
+                "Unknown calorie width {}, expected u32, u64 or u128",
+                width
+            )),
+        }
+    }
+}
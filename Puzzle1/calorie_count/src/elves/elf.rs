@@ -1,17 +1,71 @@
-pub struct Elf {
-    carried_calories: u32,
+use super::calories::Calories;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoodItem<C: Calories = u32> {
+    calories: C,
+    line: usize,
 }
-impl Elf {
+impl<C: Calories> FoodItem<C> {
+    pub fn new(calories: C, line: usize) -> Self {
+        Self { calories, line }
+    }
+    pub fn calories(&self) -> C {
+        self.calories
+    }
+    // Line (starting at 1) of the inventory the item was read from
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Elf<C: Calories = u32> {
+    name: Option<String>,
+    carried_calories: C,
+    items: Vec<FoodItem<C>>,
+}
+impl<C: Calories> Elf<C> {
     pub fn new() -> Self {
         Self {
-            carried_calories: 0,
+            name: None,
+            carried_calories: C::default(),
+            items: vec![],
+        }
+    }
+    pub fn named(name: String) -> Self {
+        Self {
+            name: Some(name),
+            carried_calories: C::default(),
+            items: vec![],
         }
     }
-    pub fn add_calories(&mut self, calories: u32) {
-        self.carried_calories += calories;
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub fn set_name(&mut self, name: String) {
+        self.name = Some(name);
     }
-    // u32 implements copy
-    pub fn calories(&self) -> u32 {
+    // Returns the new total, or None (leaving the elf untouched) if it doesn't fit in C
+    pub fn add_calories(&mut self, calories: C, line: usize) -> Option<C> {
+        self.carried_calories = self.carried_calories.checked_add(calories)?;
+        self.items.push(FoodItem::new(calories, line));
+        Some(self.carried_calories)
+    }
+    // Calories implements copy
+    pub fn calories(&self) -> C {
         self.carried_calories
     }
+    pub fn items(&self) -> &[FoodItem<C>] {
+        &self.items
+    }
+    pub fn heaviest_item(&self) -> Option<&FoodItem<C>> {
+        // max_by_key returns the last maximum, we want the first one we read
+        self.items.iter().reduce(|heaviest, item| {
+            if item.calories() > heaviest.calories() {
+                item
+            } else {
+                heaviest
+            }
+        })
+    }
 }
@@ -1,16 +1,70 @@
-use std::num::ParseIntError;
-
 #[derive(Debug)]
 pub enum ElfError {
     UnableToLoad(String),
-    UnableToOtainCalories(String),
+    InvalidCalories {
+        line: usize,
+        column: usize,
+        token: String,
+        reason: String,
+    },
+    // elf is the position of the elf in the inventory, starting at 0
+    CaloriesOverflow {
+        elf: usize,
+        line: usize,
+    },
+    UnableToRebalance(String),
+    InvalidRecord {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+}
+
+impl ElfError {
+    // line and column start at 1, field is the raw text holding the calories and field_start the
+    // number of characters before it on the line, column points to the first character of the token
+    pub fn invalid_calories(line: usize, field: &str, field_start: usize, reason: String) -> Self {
+        let token = field.trim();
+        let leading_whitespace = field.chars().count() - field.trim_start().chars().count();
+        let column = field_start + leading_whitespace + 1;
+        Self::InvalidCalories {
+            line,
+            column,
+            token: token.to_string(),
+            reason,
+        }
+    }
 }
 
 impl std::fmt::Display for ElfError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::UnableToLoad(error) => write!(f, "Unable to load elves: {}", error),
-            Self::UnableToOtainCalories(error) => write!(f, "Invalid calories: {}", error),
+            Self::InvalidCalories {
+                line,
+                column,
+                token,
+                reason,
+            } => write!(
+                f,
+                "Invalid calories at line {}, column {}: \"{}\" ({})",
+                line, column, token, reason
+            ),
+            Self::CaloriesOverflow { elf, line } => write!(
+                f,
+                "Calories of elf {} overflow at line {}, try a wider calorie type",
+                elf, line
+            ),
+            Self::UnableToRebalance(error) => write!(f, "Unable to rebalance elves: {}", error),
+            Self::InvalidRecord {
+                line,
+                column,
+                reason,
+            } => write!(
+                f,
+                "Invalid record at line {}, column {}: {}",
+                line, column, reason
+            ),
         }
     }
 }
@@ -20,9 +74,3 @@ impl std::convert::From<std::io::Error> for ElfError {
         Self::UnableToLoad(format!("Unable to load elves file: {}", error))
     }
 }
-
-impl std::convert::From<ParseIntError> for ElfError {
-    fn from(e: ParseIntError) -> Self {
-        Self::UnableToOtainCalories(format!("Couldn't get calories: {}", e))
-    }
-}
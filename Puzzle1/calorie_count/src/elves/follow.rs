@@ -0,0 +1,59 @@
+use std::io::{self, Read};
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Never reports the end of the input: once everything has been read it waits for more to be
+// appended, like tail -f. Wrapped in a BufReader it can be given to any ElfReader, which then
+// only gives back an elf once something (a blank line, a header, the next csv row) closes it
+pub struct FollowReader<R: Read> {
+    inner: R,
+    poll_interval: Duration,
+    caught_up: bool,
+    // Called once, the first time everything already in the file has been read
+    on_caught_up: Option<Box<dyn FnMut()>>,
+}
+
+impl<R: Read> FollowReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_poll_interval(inner, DEFAULT_POLL_INTERVAL)
+    }
+    pub fn with_poll_interval(inner: R, poll_interval: Duration) -> Self {
+        Self {
+            inner,
+            poll_interval,
+            caught_up: false,
+            on_caught_up: None,
+        }
+    }
+    // The reader blocks while it waits, so this is the only chance to act on what was read so far
+    pub fn on_caught_up<F: FnMut() + 'static>(mut self, callback: F) -> Self {
+        self.on_caught_up = Some(Box::new(callback));
+        self
+    }
+    pub fn caught_up(&self) -> bool {
+        self.caught_up
+    }
+}
+
+impl<R: Read> Read for FollowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let read_bytes = self.inner.read(buf)?;
+            if read_bytes > 0 {
+                return Ok(read_bytes);
+            }
+            if !self.caught_up {
+                self.caught_up = true;
+                if let Some(mut callback) = self.on_caught_up.take() {
+                    callback();
+                }
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
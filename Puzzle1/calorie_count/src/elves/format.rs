@@ -0,0 +1,44 @@
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryFormat {
+    // Blocks of calories separated by blank lines, with optional "# Name" headers and "//" comments
+    Blocks,
+    // "elf,calories" rows, consecutive rows for the same elf belong together
+    Csv,
+    // An array of elves or one elf after the other, each one either an array of calories
+    // or an object like {"name": "Alice", "items": [1000, 2000]}
+    Json,
+}
+
+impl InventoryFormat {
+    // Anything that isn't .csv or .json is read as blocks
+    pub fn from_path(filepath: &str) -> Self {
+        let extension = Path::new(filepath)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Self::Csv,
+            Some("json") | Some("jsonl") => Self::Json,
+            _ => Self::Blocks,
+        }
+    }
+}
+
+impl FromStr for InventoryFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "blocks" | "text" => Ok(Self::Blocks),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown inventory format {}, expected blocks, csv or json",
+                format
+            )),
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use super::calories::Calories;
+use super::elf::Elf;
+use super::errors::ElfError;
+use super::loader::{self, Diagnostics};
+use super::source::LineSource;
+use std::io::BufRead;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonState {
+    Start,
+    // Inside a top level array of elves
+    FirstInArray,
+    InArray,
+    // Elves one after the other, like JSON lines
+    Stream,
+    Done,
+}
+
+fn syntax_error<R: BufRead>(source: &LineSource<R>, reason: String) -> ElfError {
+    ElfError::InvalidRecord {
+        line: source.line_number(),
+        column: source.column(),
+        reason,
+    }
+}
+
+fn describe(next_char: Option<char>) -> String {
+    match next_char {
+        Some(next_char) => format!("'{}'", next_char),
+        None => String::from("the end of the input"),
+    }
+}
+
+// Skips whitespace (moving to the next lines if needed) and returns the next character without consuming it
+fn peek<R: BufRead>(source: &mut LineSource<R>) -> Result<Option<char>, ElfError> {
+    loop {
+        let (skipped, next_char) = {
+            let rest = source.rest();
+            let trimmed = rest.trim_start();
+            (rest.len() - trimmed.len(), trimmed.chars().next())
+        };
+        source.consume(skipped);
+        if next_char.is_some() {
+            return Ok(next_char);
+        }
+        if !source.advance()? {
+            return Ok(None);
+        }
+    }
+}
+
+fn expect<R: BufRead>(source: &mut LineSource<R>, expected: char) -> Result<(), ElfError> {
+    match peek(source)? {
+        Some(next_char) if next_char == expected => {
+            source.consume(next_char.len_utf8());
+            Ok(())
+        }
+        next_char => Err(syntax_error(
+            source,
+            format!("expected '{}', found {}", expected, describe(next_char)),
+        )),
+    }
+}
+
+// Numbers, but also true/false/null and garbage, which then fails when parsed as calories
+fn token_length(rest: &str) -> usize {
+    rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.'))
+        .unwrap_or(rest.len())
+}
+
+fn parse_string<R: BufRead>(source: &mut LineSource<R>) -> Result<String, ElfError> {
+    expect(source, '"')?;
+    let mut value = String::new();
+    let mut chars = source.rest().char_indices();
+    let length = loop {
+        match chars.next() {
+            Some((position, '"')) => break position + 1,
+            Some((_, '\\')) => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 'b')) => value.push('\u{8}'),
+                Some((_, 'f')) => value.push('\u{c}'),
+                Some((_, 'u')) => {
+                    let code: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                    let code_point = u32::from_str_radix(&code, 16).map_err(|_| {
+                        syntax_error(source, format!("invalid unicode escape \\u{}", code))
+                    })?;
+                    value.push(char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                Some((_, escaped)) if escaped == '"' || escaped == '\\' || escaped == '/' => {
+                    value.push(escaped)
+                }
+                escaped => {
+                    return Err(syntax_error(
+                        source,
+                        format!(
+                            "invalid escape sequence \\{}",
+                            escaped.map(|(_, c)| c).unwrap_or(' ')
+                        ),
+                    ))
+                }
+            },
+            Some((_, next_char)) => value.push(next_char),
+            None => return Err(syntax_error(source, String::from("unterminated string"))),
+        }
+    };
+    source.consume(length);
+    Ok(value)
+}
+
+fn skip_value<R: BufRead>(source: &mut LineSource<R>) -> Result<(), ElfError> {
+    match peek(source)? {
+        Some('"') => parse_string(source).map(|_| ()),
+        Some(open @ ('{' | '[')) => {
+            let close = if open == '{' { '}' } else { ']' };
+            source.consume(1);
+            if peek(source)? == Some(close) {
+                source.consume(1);
+                return Ok(());
+            }
+            loop {
+                if open == '{' {
+                    parse_string(source)?;
+                    expect(source, ':')?;
+                }
+                skip_value(source)?;
+                match peek(source)? {
+                    Some(',') => source.consume(1),
+                    Some(next_char) if next_char == close => {
+                        source.consume(1);
+                        return Ok(());
+                    }
+                    next_char => {
+                        return Err(syntax_error(
+                            source,
+                            format!("expected ',' or '{}', found {}", close, describe(next_char)),
+                        ))
+                    }
+                }
+            }
+        }
+        next_char => {
+            let length = token_length(source.rest());
+            if length == 0 {
+                return Err(syntax_error(
+                    source,
+                    format!("expected a value, found {}", describe(next_char)),
+                ));
+            }
+            source.consume(length);
+            Ok(())
+        }
+    }
+}
+
+// The opening bracket has already been consumed
+fn parse_items<R: BufRead, C: Calories>(
+    source: &mut LineSource<R>,
+    elf: &mut Elf<C>,
+    elf_index: usize,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), ElfError> {
+    if peek(source)? == Some(']') {
+        source.consume(1);
+        return Ok(());
+    }
+    loop {
+        let next_char = peek(source)?;
+        let result = if next_char == Some('"') {
+            // A quoted item is checked like a bare one, its text starts right after the quote
+            let line_number = source.line_number();
+            let field_start = source.column();
+            let field = parse_string(source)?;
+            loader::add_item(elf, elf_index, line_number, &field, field_start)
+        } else {
+            let length = token_length(source.rest());
+            if length == 0 {
+                return Err(syntax_error(
+                    source,
+                    format!("expected calories, found {}", describe(next_char)),
+                ));
+            }
+            let result = loader::add_item(
+                elf,
+                elf_index,
+                source.line_number(),
+                &source.rest()[..length],
+                source.column() - 1,
+            );
+            source.consume(length);
+            result
+        };
+        if let Err(error) = result {
+            diagnostics.report(error)?;
+        }
+        match peek(source)? {
+            Some(',') => source.consume(1),
+            Some(']') => {
+                source.consume(1);
+                return Ok(());
+            }
+            next_char => {
+                return Err(syntax_error(
+                    source,
+                    format!("expected ',' or ']', found {}", describe(next_char)),
+                ))
+            }
+        }
+    }
+}
+
+fn parse_elf<R: BufRead, C: Calories>(
+    source: &mut LineSource<R>,
+    elf_index: usize,
+    diagnostics: &mut Diagnostics,
+) -> Result<Elf<C>, ElfError> {
+    let mut elf = Elf::new();
+    match peek(source)? {
+        Some('[') => {
+            source.consume(1);
+            parse_items(source, &mut elf, elf_index, diagnostics)?;
+        }
+        Some('{') => {
+            source.consume(1);
+            if peek(source)? == Some('}') {
+                source.consume(1);
+                return Ok(elf);
+            }
+            loop {
+                let key = parse_string(source)?;
+                expect(source, ':')?;
+                match key.as_str() {
+                    "name" if peek(source)? == Some('"') => elf.set_name(parse_string(source)?),
+                    "items" | "calories" => {
+                        expect(source, '[')?;
+                        parse_items(source, &mut elf, elf_index, diagnostics)?;
+                    }
+                    _ => skip_value(source)?,
+                }
+                match peek(source)? {
+                    Some(',') => source.consume(1),
+                    Some('}') => {
+                        source.consume(1);
+                        break;
+                    }
+                    next_char => {
+                        return Err(syntax_error(
+                            source,
+                            format!("expected ',' or '}}', found {}", describe(next_char)),
+                        ))
+                    }
+                }
+            }
+        }
+        next_char => {
+            return Err(syntax_error(
+                source,
+                format!("expected an elf, found {}", describe(next_char)),
+            ))
+        }
+    }
+    Ok(elf)
+}
+
+// Nothing but whitespace may follow the array of elves
+fn end_of_inventory<R: BufRead, C: Calories>(
+    source: &mut LineSource<R>,
+) -> Result<Option<Elf<C>>, ElfError> {
+    match peek(source)? {
+        None => Ok(None),
+        next_char => Err(syntax_error(
+            source,
+            format!(
+                "expected the end of the inventory, found {}",
+                describe(next_char)
+            ),
+        )),
+    }
+}
+
+// Syntax errors can't be skipped, so they end the inventory even in lenient mode
+pub fn next_elf<R: BufRead, C: Calories>(
+    source: &mut LineSource<R>,
+    state: &mut JsonState,
+    elf_index: usize,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<Elf<C>>, ElfError> {
+    let elf = read_next_elf(source, state, elf_index, diagnostics);
+    if elf.is_err() {
+        *state = JsonState::Done;
+    }
+    elf
+}
+
+fn read_next_elf<R: BufRead, C: Calories>(
+    source: &mut LineSource<R>,
+    state: &mut JsonState,
+    elf_index: usize,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<Elf<C>>, ElfError> {
+    if *state == JsonState::Start {
+        match peek(source)? {
+            None => {
+                *state = JsonState::Done;
+                return Ok(None);
+            }
+            Some('[') => {
+                source.consume(1);
+                // "[[" or "[{" is an array of elves, "[1" is the first of several elves given as arrays.
+                // "[]" is an empty inventory when nothing follows it, otherwise an elf with no items
+                match peek(source)? {
+                    Some('[') | Some('{') => *state = JsonState::FirstInArray,
+                    Some(']') => {
+                        source.consume(1);
+                        if peek(source)?.is_none() {
+                            *state = JsonState::Done;
+                            return Ok(None);
+                        }
+                        *state = JsonState::Stream;
+                        return Ok(Some(Elf::new()));
+                    }
+                    _ => {
+                        *state = JsonState::Stream;
+                        let mut elf = Elf::new();
+                        parse_items(source, &mut elf, elf_index, diagnostics)?;
+                        return Ok(Some(elf));
+                    }
+                }
+            }
+            Some(_) => *state = JsonState::Stream,
+        }
+    }
+    match *state {
+        JsonState::FirstInArray => {
+            if peek(source)? == Some(']') {
+                source.consume(1);
+                *state = JsonState::Done;
+                return end_of_inventory(source);
+            }
+            *state = JsonState::InArray;
+            parse_elf(source, elf_index, diagnostics).map(Some)
+        }
+        JsonState::InArray => match peek(source)? {
+            Some(',') => {
+                source.consume(1);
+                parse_elf(source, elf_index, diagnostics).map(Some)
+            }
+            Some(']') => {
+                source.consume(1);
+                *state = JsonState::Done;
+                end_of_inventory(source)
+            }
+            next_char => Err(syntax_error(
+                source,
+                format!("expected ',' or ']', found {}", describe(next_char)),
+            )),
+        },
+        JsonState::Stream => match peek(source)? {
+            None => {
+                *state = JsonState::Done;
+                Ok(None)
+            }
+            Some(_) => parse_elf(source, elf_index, diagnostics).map(Some),
+        },
+        JsonState::Start | JsonState::Done => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elves::format::InventoryFormat;
+    use crate::elves::loader::{ElfReader, LoadMode};
+
+    fn read_elves(json: &str, mode: LoadMode) -> (Vec<Elf>, Option<ElfError>, usize) {
+        let mut elves_reader = ElfReader::with_format(json.as_bytes(), InventoryFormat::Json, mode);
+        let mut elves = vec![];
+        let mut error = None;
+        for elf in elves_reader.by_ref() {
+            match elf {
+                Ok(elf) => elves.push(elf),
+                Err(elf_error) => error = Some(elf_error),
+            }
+        }
+        (elves, error, elves_reader.diagnostics().len())
+    }
+
+    fn calories(elves: &[Elf]) -> Vec<Vec<u32>> {
+        elves
+            .iter()
+            .map(|elf| elf.items().iter().map(|item| item.calories()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_array_of_elves() {
+        let (elves, error, _) = read_elves(
+            "[[1000, 2000],\n {\"name\": \"Bob\", \"items\": [3000]}]",
+            LoadMode::Strict,
+        );
+        assert!(error.is_none());
+        assert_eq!(vec![vec![1000, 2000], vec![3000]], calories(&elves));
+        assert_eq!(Some("Bob"), elves[1].name());
+    }
+    #[test]
+    fn test_stream_of_elves() {
+        let (elves, error, _) = read_elves("[1, 2]\n[3]\n{\"calories\": [4]}\n", LoadMode::Strict);
+        assert!(error.is_none());
+        assert_eq!(vec![vec![1, 2], vec![3], vec![4]], calories(&elves));
+    }
+    #[test]
+    fn test_leading_empty_array() {
+        let (elves, error, _) = read_elves("[]\n[5]\n", LoadMode::Strict);
+        assert!(error.is_none());
+        assert_eq!(vec![Vec::<u32>::new(), vec![5]], calories(&elves));
+        let (elves, error, _) = read_elves("[]\n", LoadMode::Strict);
+        assert!(error.is_none());
+        assert_eq!(0, elves.len());
+    }
+    #[test]
+    fn test_trailing_input() {
+        let (elves, error, _) = read_elves("[[1], [2]] [3]", LoadMode::Lenient);
+        assert_eq!(vec![vec![1], vec![2]], calories(&elves));
+        assert!(matches!(
+            error,
+            Some(ElfError::InvalidRecord {
+                line: 1,
+                column: 12,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn test_string_escapes() {
+        let (elves, error, _) = read_elves(
+            r#"[{"name": "Zo\u00eb \"the\" \\ elf", "items": [1]}]"#,
+            LoadMode::Strict,
+        );
+        assert!(error.is_none());
+        assert_eq!(Some("Zoë \"the\" \\ elf"), elves[0].name());
+        let (elves, error, _) = read_elves(r#"[{"name": "\q", "items": [1]}]"#, LoadMode::Strict);
+        assert_eq!(0, elves.len());
+        assert!(matches!(error, Some(ElfError::InvalidRecord { .. })));
+    }
+    #[test]
+    fn test_quoted_items() {
+        let (elves, error, skipped) = read_elves(r#"[[3, "x"], ["3000"]]"#, LoadMode::Lenient);
+        assert!(error.is_none());
+        assert_eq!(1, skipped);
+        assert_eq!(vec![vec![3], vec![3000]], calories(&elves));
+        let (_, error, _) = read_elves(r#"[[3, "x"], ["3000"]]"#, LoadMode::Strict);
+        assert!(matches!(
+            error,
+            Some(ElfError::InvalidCalories {
+                line: 1,
+                column: 7,
+                ..
+            })
+        ));
+    }
+}
@@ -1,19 +1,291 @@
+use super::calories::Calories;
 use super::elf::Elf;
 use super::errors;
-use std::fs;
-
-pub fn load_elves_from_file(filepath: &str) -> Result<Vec<Elf>, errors::ElfError> {
-    let mut elves = vec![];
-    let elves_file = fs::read_to_string(filepath)?;
-    let mut elf = Elf::new();
-    for line in elves_file.lines() {
-        if line.trim().is_empty() {
-            elves.push(elf);
-            elf = Elf::new();
-            continue;
+use super::follow::FollowReader;
+use super::format::InventoryFormat;
+use super::json::{self, JsonState};
+use super::source::LineSource;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    // Stops at the first invalid line
+    Strict,
+    // Skips invalid lines and keeps them in the reader's diagnostics
+    Lenient,
+}
+
+pub struct Diagnostics {
+    mode: LoadMode,
+    errors: Vec<errors::ElfError>,
+}
+
+impl Diagnostics {
+    pub fn new(mode: LoadMode) -> Self {
+        Self {
+            mode,
+            errors: vec![],
+        }
+    }
+    // Keeps the error in lenient mode, gives it back in strict mode
+    pub fn report(&mut self, error: errors::ElfError) -> Result<(), errors::ElfError> {
+        match self.mode {
+            LoadMode::Strict => Err(error),
+            LoadMode::Lenient => {
+                self.errors.push(error);
+                Ok(())
+            }
         }
-        let calories: u32 = line.trim().parse()?;
-        elf.add_calories(calories);
     }
-    Ok(elves)
+    pub fn errors(&self) -> &[errors::ElfError] {
+        &self.errors
+    }
+}
+
+// field is the raw text holding the calories and field_start the number of characters before it
+pub fn add_item<C: Calories>(
+    elf: &mut Elf<C>,
+    elf_index: usize,
+    line_number: usize,
+    field: &str,
+    field_start: usize,
+) -> Result<(), errors::ElfError> {
+    let calories = field.trim().parse::<C>().map_err(|error| {
+        errors::ElfError::invalid_calories(line_number, field, field_start, error.to_string())
+    })?;
+    match elf.add_calories(calories, line_number) {
+        Some(_) => Ok(()),
+        None => Err(errors::ElfError::CaloriesOverflow {
+            elf: elf_index,
+            line: line_number,
+        }),
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find("//") {
+        Some(comment_start) => &line[..comment_start],
+        None => line,
+    }
+}
+
+pub struct ElfReader<R: BufRead, C: Calories = u32> {
+    source: LineSource<R>,
+    format: InventoryFormat,
+    elf_index: usize,
+    diagnostics: Diagnostics,
+    // Elf already started by a line that also closed the previous one
+    pending: Option<Elf<C>>,
+    csv_header_checked: bool,
+    json_state: JsonState,
+}
+
+impl<R: BufRead, C: Calories> ElfReader<R, C> {
+    pub fn new(reader: R) -> Self {
+        Self::with_mode(reader, LoadMode::Strict)
+    }
+    pub fn with_mode(reader: R, mode: LoadMode) -> Self {
+        Self::with_format(reader, InventoryFormat::Blocks, mode)
+    }
+    pub fn with_format(reader: R, format: InventoryFormat, mode: LoadMode) -> Self {
+        Self {
+            source: LineSource::new(reader),
+            format,
+            elf_index: 0,
+            diagnostics: Diagnostics::new(mode),
+            pending: None,
+            csv_header_checked: false,
+            json_state: JsonState::Start,
+        }
+    }
+    // Invalid lines skipped so far, always empty in strict mode
+    pub fn diagnostics(&self) -> &[errors::ElfError] {
+        self.diagnostics.errors()
+    }
+    pub fn into_diagnostics(self) -> Vec<errors::ElfError> {
+        self.diagnostics.errors
+    }
+
+    // Blank lines close the current elf (runs of them don't create empty elves) and so does a
+    // "# Name" header, which also names the next one. The last elf doesn't need a blank line after it
+    fn next_block_elf(&mut self) -> Result<Option<Elf<C>>, errors::ElfError> {
+        let mut started = self.pending.is_some();
+        let mut elf = self.pending.take().unwrap_or_default();
+        loop {
+            if !self.source.advance()? {
+                return Ok(started.then_some(elf));
+            }
+            let line = self.source.line();
+            let content = strip_comment(line);
+            if content.trim().is_empty() {
+                if line.trim().is_empty() && started {
+                    return Ok(Some(elf));
+                }
+                continue;
+            }
+            if let Some(name) = content.trim().strip_prefix('#') {
+                let name = name.trim();
+                let named_elf = if name.is_empty() {
+                    Elf::new()
+                } else {
+                    Elf::named(name.to_string())
+                };
+                if started {
+                    self.pending = Some(named_elf);
+                    return Ok(Some(elf));
+                }
+                elf = named_elf;
+                started = true;
+                continue;
+            }
+            started = true;
+            let line_number = self.source.line_number();
+            if let Err(error) = add_item(&mut elf, self.elf_index, line_number, content, 0) {
+                self.diagnostics.report(error)?;
+            }
+        }
+    }
+
+    // An optional "elf,calories" header, then one row per item. Rows with an empty elf belong to
+    // unnamed elves, so only consecutive unnamed rows end up in the same one
+    fn next_csv_elf(&mut self) -> Result<Option<Elf<C>>, errors::ElfError> {
+        let mut elf = self.pending.take();
+        loop {
+            if !self.source.advance()? {
+                return Ok(elf);
+            }
+            let line = self.source.line();
+            if line.trim().is_empty() || line.trim_start().starts_with("//") {
+                continue;
+            }
+            let line_number = self.source.line_number();
+            let fields = line.split(',').collect::<Vec<_>>();
+            if fields.len() != 2 {
+                self.diagnostics.report(errors::ElfError::InvalidRecord {
+                    line: line_number,
+                    column: 1,
+                    reason: format!("expected 2 fields, found {}", fields.len()),
+                })?;
+                continue;
+            }
+            if !self.csv_header_checked {
+                self.csv_header_checked = true;
+                if fields[1].trim().eq_ignore_ascii_case("calories") {
+                    continue;
+                }
+            }
+            let name = fields[0].trim();
+            let name = (!name.is_empty()).then_some(name);
+            let calories_start = fields[0].chars().count() + 1;
+            let (mut current, finished) = match elf.take() {
+                Some(current) if current.name() == name => (current, None),
+                finished => (
+                    name.map_or_else(Elf::new, |name| Elf::named(name.to_string())),
+                    finished,
+                ),
+            };
+            // When the previous elf is done the item belongs to the one after it
+            let elf_index = self.elf_index + finished.is_some() as usize;
+            let result = add_item(
+                &mut current,
+                elf_index,
+                line_number,
+                fields[1],
+                calories_start,
+            );
+            if let Err(error) = result {
+                self.diagnostics.report(error)?;
+            }
+            if finished.is_some() {
+                self.pending = Some(current);
+                return Ok(finished);
+            }
+            elf = Some(current);
+        }
+    }
+}
+
+impl<R: BufRead, C: Calories> Iterator for ElfReader<R, C> {
+    type Item = Result<Elf<C>, errors::ElfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let elf = match self.format {
+            InventoryFormat::Blocks => self.next_block_elf(),
+            InventoryFormat::Csv => self.next_csv_elf(),
+            InventoryFormat::Json => json::next_elf(
+                &mut self.source,
+                &mut self.json_state,
+                self.elf_index,
+                &mut self.diagnostics,
+            ),
+        };
+        match elf {
+            Ok(Some(elf)) => {
+                self.elf_index += 1;
+                Some(Ok(elf))
+            }
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+pub fn load_elves<R: BufRead, C: Calories>(reader: R) -> ElfReader<R, C> {
+    ElfReader::new(reader)
+}
+
+pub fn load_elves_lenient<R: BufRead, C: Calories>(reader: R) -> ElfReader<R, C> {
+    ElfReader::with_mode(reader, LoadMode::Lenient)
+}
+
+pub fn open_elves_file<C: Calories>(
+    filepath: &str,
+    format: InventoryFormat,
+    mode: LoadMode,
+) -> Result<ElfReader<BufReader<File>, C>, errors::ElfError> {
+    let elves_file = File::open(filepath)?;
+    Ok(ElfReader::with_format(
+        BufReader::new(elves_file),
+        format,
+        mode,
+    ))
+}
+
+// Reads the file from the start and then waits for new elves to be appended to it
+// on_caught_up runs once all the elves already in the file have been read
+pub fn follow_elves_file<C: Calories, F: FnMut() + 'static>(
+    filepath: &str,
+    format: InventoryFormat,
+    mode: LoadMode,
+    on_caught_up: F,
+) -> Result<ElfReader<BufReader<FollowReader<File>>, C>, errors::ElfError> {
+    let elves_file = File::open(filepath)?;
+    Ok(ElfReader::with_format(
+        BufReader::new(FollowReader::new(elves_file).on_caught_up(on_caught_up)),
+        format,
+        mode,
+    ))
+}
+
+pub fn open_elves_stdin<C: Calories>(
+    format: InventoryFormat,
+    mode: LoadMode,
+) -> ElfReader<io::StdinLock<'static>, C> {
+    ElfReader::with_format(io::stdin().lock(), format, mode)
+}
+
+pub fn load_elves_from_file<C: Calories>(filepath: &str) -> Result<Vec<Elf<C>>, errors::ElfError> {
+    let format = InventoryFormat::from_path(filepath);
+    open_elves_file(filepath, format, LoadMode::Strict)?.collect()
+}
+
+// Returns every elf that could be parsed along with the problems found on the way
+pub fn load_elves_from_file_lenient<C: Calories>(
+    filepath: &str,
+) -> Result<(Vec<Elf<C>>, Vec<errors::ElfError>), errors::ElfError> {
+    let format = InventoryFormat::from_path(filepath);
+    let mut elves_reader = open_elves_file(filepath, format, LoadMode::Lenient)?;
+    let elves = elves_reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    Ok((elves, elves_reader.into_diagnostics()))
 }
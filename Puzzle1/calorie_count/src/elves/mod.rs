@@ -1,3 +1,12 @@
+pub mod calories;
 pub mod elf;
 pub mod errors;
+pub mod follow;
+pub mod format;
+pub mod json;
 pub mod loader;
+pub mod ranking;
+pub mod rebalance;
+pub mod report;
+pub mod source;
+pub mod statistics;
@@ -0,0 +1,119 @@
+use super::calories::Calories;
+use super::elf::Elf;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+#[derive(Clone)]
+pub struct RankedElf<C: Calories = u32> {
+    index: usize,
+    elf: Elf<C>,
+}
+
+impl<C: Calories> RankedElf<C> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    pub fn elf(&self) -> &Elf<C> {
+        &self.elf
+    }
+    pub fn calories(&self) -> C {
+        self.elf.calories()
+    }
+}
+
+// An elf ranks higher when it carries more calories; on ties the one that came first wins
+impl<C: Calories> Ord for RankedElf<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.calories()
+            .cmp(&other.calories())
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl<C: Calories> PartialOrd for RankedElf<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Calories> PartialEq for RankedElf<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Calories> Eq for RankedElf<C> {}
+
+// Keeps at most size elves in memory: the heap is a min-heap, so its top is always the weakest
+// of the current top and the one we have to drop when a better elf shows up
+pub struct Leaderboard<C: Calories = u32> {
+    heap: BinaryHeap<Reverse<RankedElf<C>>>,
+    size: usize,
+    next_index: usize,
+}
+
+impl<C: Calories> Leaderboard<C> {
+    pub fn new(size: usize) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(size + 1),
+            size,
+            next_index: 0,
+        }
+    }
+    // Elves get their index in the order they are pushed, returns whether the top changed
+    pub fn push(&mut self, elf: Elf<C>) -> bool {
+        let ranked_elf = RankedElf {
+            index: self.next_index,
+            elf,
+        };
+        self.next_index += 1;
+        if self.size == 0 {
+            return false;
+        }
+        if self.heap.len() < self.size {
+            self.heap.push(Reverse(ranked_elf));
+            return true;
+        }
+        match self.heap.peek_mut() {
+            Some(mut weakest) if ranked_elf > weakest.0 => {
+                *weakest = Reverse(ranked_elf);
+                true
+            }
+            _ => false,
+        }
+    }
+    // Number of elves pushed so far, not only the ones in the top
+    pub fn elf_count(&self) -> usize {
+        self.next_index
+    }
+    // Strongest elf first
+    pub fn ranking(&self) -> Vec<RankedElf<C>> {
+        let mut ranking = self
+            .heap
+            .iter()
+            .map(|Reverse(ranked_elf)| ranked_elf.clone())
+            .collect::<Vec<_>>();
+        ranking.sort_by(|x, y| y.cmp(x));
+        ranking
+    }
+    pub fn into_ranking(self) -> Vec<RankedElf<C>> {
+        // Sorting Reverse ascending leaves the strongest elf first
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ranked_elf)| ranked_elf)
+            .collect()
+    }
+}
+
+pub fn top_n<I, C, E>(elves: I, n: usize) -> Result<Vec<RankedElf<C>>, E>
+where
+    I: IntoIterator<Item = Result<Elf<C>, E>>,
+    C: Calories,
+{
+    let mut leaderboard = Leaderboard::new(n);
+    for elf in elves {
+        leaderboard.push(elf?);
+    }
+    Ok(leaderboard.into_ranking())
+}
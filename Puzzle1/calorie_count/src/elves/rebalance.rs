@@ -0,0 +1,446 @@
+use super::calories::Calories;
+use super::elf::{Elf, FoodItem};
+use super::errors::ElfError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebalanceGoal {
+    // Make the totals as close to each other as possible
+    Even,
+    // Every elf has to end up carrying at most this many calories
+    Cap(u128),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer<C: Calories = u32> {
+    item: FoodItem<C>,
+    from: usize,
+    to: usize,
+}
+
+impl<C: Calories> Transfer<C> {
+    pub fn item(&self) -> &FoodItem<C> {
+        &self.item
+    }
+    pub fn from(&self) -> usize {
+        self.from
+    }
+    pub fn to(&self) -> usize {
+        self.to
+    }
+}
+
+pub struct RebalancePlan<C: Calories = u32> {
+    transfers: Vec<Transfer<C>>,
+    totals: Vec<u128>,
+    minimal: bool,
+}
+
+impl<C: Calories> RebalancePlan<C> {
+    pub fn transfers(&self) -> &[Transfer<C>] {
+        &self.transfers
+    }
+    // Totals of every elf once the transfers are done, in the same order as the input
+    pub fn totals(&self) -> &[u128] {
+        &self.totals
+    }
+    pub fn spread(&self) -> u128 {
+        let max = self.totals.iter().max().copied().unwrap_or(0);
+        let min = self.totals.iter().min().copied().unwrap_or(0);
+        max - min
+    }
+    // True when the plan came from the exhaustive search, so no plan does better
+    pub fn is_minimal(&self) -> bool {
+        self.minimal
+    }
+}
+
+// Each item remembers the elf it started with, so items moved more than once still become a single transfer
+struct Holding<C: Calories> {
+    items: Vec<(usize, FoodItem<C>)>,
+    total: u128,
+}
+
+impl<C: Calories> Holding<C> {
+    fn take(&mut self, position: usize) -> (usize, FoodItem<C>) {
+        let item = self.items.swap_remove(position);
+        self.total -= item.1.calories().into();
+        item
+    }
+    fn give(&mut self, item: (usize, FoodItem<C>)) -> Result<(), ElfError> {
+        self.total = self
+            .total
+            .checked_add(item.1.calories().into())
+            .ok_or_else(|| {
+                ElfError::UnableToRebalance(format!(
+                    "the item at line {} takes an elf over {} calories",
+                    item.1.line(),
+                    u128::MAX
+                ))
+            })?;
+        self.items.push(item);
+        Ok(())
+    }
+}
+
+// Trying every elf for every item is only done while it stays under this many assignments
+const EXACT_SEARCH_LIMIT: u128 = 1 << 20;
+
+// Small inventories get an exhaustive search: Even gets the smallest possible spread with the fewest
+// transfers that reach it, Cap the fewest transfers, and a cap is only refused when no plan exists.
+// Bigger ones fall back to a greedy heuristic that always moves one item to the lightest elf, which
+// guarantees neither and can give up on a cap that's reachable. Either way an item shows up at most
+// once, going from its original elf to its final one
+pub fn plan_rebalance<C: Calories>(
+    elves: &[Elf<C>],
+    goal: RebalanceGoal,
+) -> Result<RebalancePlan<C>, ElfError> {
+    let mut holdings: Vec<Holding<C>> = elves
+        .iter()
+        .enumerate()
+        .map(|(index, elf)| Holding {
+            items: elf.items().iter().map(|item| (index, *item)).collect(),
+            total: elf.calories().into(),
+        })
+        .collect();
+    if let RebalanceGoal::Cap(cap) = goal {
+        check_cap(&holdings, cap)?;
+    }
+    let item_count = holdings
+        .iter()
+        .map(|holding| holding.items.len())
+        .sum::<usize>();
+    let minimal = u32::try_from(item_count)
+        .ok()
+        .and_then(|item_count| (holdings.len() as u128).checked_pow(item_count))
+        .is_some_and(|assignments| assignments <= EXACT_SEARCH_LIMIT);
+    if minimal {
+        holdings = search_exact(holdings, goal)?;
+    } else {
+        match goal {
+            RebalanceGoal::Even => even_out(&mut holdings)?,
+            RebalanceGoal::Cap(cap) => cap_totals(&mut holdings, cap)?,
+        }
+    }
+    let mut transfers = vec![];
+    for (to, holding) in holdings.iter().enumerate() {
+        for (from, item) in holding.items.iter() {
+            if *from != to {
+                transfers.push(Transfer {
+                    item: *item,
+                    from: *from,
+                    to,
+                });
+            }
+        }
+    }
+    transfers.sort_by_key(|transfer| (transfer.from, transfer.item.line()));
+    Ok(RebalancePlan {
+        transfers,
+        totals: holdings.iter().map(|holding| holding.total).collect(),
+        minimal,
+    })
+}
+
+// Depth first over the items, heaviest first, every item trying its own elf before the others
+struct ExactSearch<C: Calories> {
+    items: Vec<(usize, FoodItem<C>)>,
+    // Calories of the items from this position on
+    remaining: Vec<u128>,
+    goal: RebalanceGoal,
+    totals: Vec<u128>,
+    elves: Vec<usize>,
+    // (spread, transfers) of the best complete assignment, the spread is always 0 for a cap
+    best: Option<((u128, usize), Vec<usize>)>,
+}
+
+impl<C: Calories> ExactSearch<C> {
+    fn spread(&self) -> u128 {
+        let max = self.totals.iter().max().copied().unwrap_or(0);
+        let min = self.totals.iter().min().copied().unwrap_or(0);
+        max - min
+    }
+    fn visit(&mut self, position: usize, transfers: usize) {
+        // The lightest elf can at most get everything that's left, and the heaviest can only grow
+        let lowest_spread = match self.goal {
+            RebalanceGoal::Even => self.spread().saturating_sub(self.remaining[position]),
+            RebalanceGoal::Cap(_) => 0,
+        };
+        if let Some((best, _)) = &self.best {
+            if (lowest_spread, transfers) >= *best {
+                return;
+            }
+        }
+        let Some((origin, item)) = self.items.get(position).copied() else {
+            self.best = Some(((lowest_spread, transfers), self.elves.clone()));
+            return;
+        };
+        let calories: u128 = item.calories().into();
+        let elf_order = std::iter::once(origin)
+            .chain((0..self.totals.len()).filter(|elf_index| *elf_index != origin));
+        for elf_index in elf_order {
+            let Some(total) = self.totals[elf_index].checked_add(calories) else {
+                continue;
+            };
+            if matches!(self.goal, RebalanceGoal::Cap(cap) if total > cap) {
+                continue;
+            }
+            self.totals[elf_index] = total;
+            self.elves.push(elf_index);
+            self.visit(position + 1, transfers + usize::from(elf_index != origin));
+            self.elves.pop();
+            self.totals[elf_index] -= calories;
+        }
+    }
+}
+
+fn search_exact<C: Calories>(
+    holdings: Vec<Holding<C>>,
+    goal: RebalanceGoal,
+) -> Result<Vec<Holding<C>>, ElfError> {
+    let elf_count = holdings.len();
+    let mut items: Vec<(usize, FoodItem<C>)> = holdings
+        .into_iter()
+        .flat_map(|holding| holding.items)
+        .collect();
+    items.sort_by_key(|(_, item)| std::cmp::Reverse(item.calories()));
+    let mut remaining = vec![0u128; items.len() + 1];
+    for position in (0..items.len()).rev() {
+        remaining[position] =
+            remaining[position + 1].saturating_add(items[position].1.calories().into());
+    }
+    let mut search = ExactSearch {
+        items,
+        remaining,
+        goal,
+        totals: vec![0; elf_count],
+        elves: vec![],
+        best: None,
+    };
+    search.visit(0, 0);
+    let Some((_, elves)) = search.best else {
+        return Err(ElfError::UnableToRebalance(match goal {
+            RebalanceGoal::Cap(cap) => format!(
+                "no way of handing out the items keeps every elf at or under {} calories",
+                cap
+            ),
+            RebalanceGoal::Even => format!("every plan takes an elf over {} calories", u128::MAX),
+        }));
+    };
+    let mut holdings: Vec<Holding<C>> = (0..elf_count)
+        .map(|_| Holding {
+            items: vec![],
+            total: 0,
+        })
+        .collect();
+    for (item, elf_index) in search.items.into_iter().zip(elves) {
+        holdings[elf_index].give(item)?;
+    }
+    Ok(holdings)
+}
+
+fn heaviest_and_lightest<C: Calories>(holdings: &[Holding<C>]) -> Option<(usize, usize)> {
+    let heaviest = (0..holdings.len()).max_by_key(|index| holdings[*index].total)?;
+    let lightest = (0..holdings.len()).min_by_key(|index| holdings[*index].total)?;
+    Some((heaviest, lightest))
+}
+
+// Full product as (high, low) halves, so products that don't fit in a u128 still compare as tuples
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    let mask = u128::from(u64::MAX);
+    let (a_high, a_low) = (a >> 64, a & mask);
+    let (b_high, b_low) = (b >> 64, b & mask);
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let middle = (low_low >> 64) + (high_low & mask) + (low_high & mask);
+    let low = (middle << 64) | (low_low & mask);
+    let high = a_high * b_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+fn even_out<C: Calories>(holdings: &mut [Holding<C>]) -> Result<(), ElfError> {
+    // Moving c calories from an elf carrying a to one carrying b lowers the sum of squared
+    // totals by 2c(d - c) with d = a - b, we always take the move that lowers it the most, so this
+    // can't go back and forth forever. The lightest elf is always the best one to receive the item.
+    // c(d - c) can be as big as (d / 2)^2, which doesn't always fit in a u128, so it's widened
+    while let Some((_, lightest)) = heaviest_and_lightest(holdings) {
+        let lightest_total = holdings[lightest].total;
+        let mut best_move = None;
+        let mut best_gain = None;
+        for (donor, holding) in holdings.iter().enumerate() {
+            let difference = holding.total - lightest_total;
+            for (position, (_, item)) in holding.items.iter().enumerate() {
+                let calories: u128 = item.calories().into();
+                if calories == 0 || calories >= difference {
+                    continue;
+                }
+                let gain = widening_mul(calories, difference - calories);
+                if best_gain.is_none_or(|best| gain > best) {
+                    best_gain = Some(gain);
+                    best_move = Some((donor, position));
+                }
+            }
+        }
+        match best_move {
+            Some((donor, position)) => {
+                let item = holdings[donor].take(position);
+                holdings[lightest].give(item)?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+// Turns down the caps that can't be met whatever the plan
+fn check_cap<C: Calories>(holdings: &[Holding<C>], cap: u128) -> Result<(), ElfError> {
+    if holdings.iter().all(|holding| holding.total <= cap) {
+        return Ok(());
+    }
+    let total = holdings
+        .iter()
+        .try_fold(0u128, |total, holding| total.checked_add(holding.total))
+        .ok_or_else(|| {
+            ElfError::UnableToRebalance(format!(
+                "the elves carry more than {} calories between them",
+                u128::MAX
+            ))
+        })?;
+    if total > cap.saturating_mul(holdings.len() as u128) {
+        return Err(ElfError::UnableToRebalance(format!(
+            "{} calories don't fit in {} elves carrying at most {} each",
+            total,
+            holdings.len(),
+            cap
+        )));
+    }
+    for holding in holdings.iter() {
+        if let Some((_, item)) = holding
+            .items
+            .iter()
+            .find(|(_, item)| item.calories().into() > cap)
+        {
+            return Err(ElfError::UnableToRebalance(format!(
+                "the item at line {} alone carries more than {} calories",
+                item.line(),
+                cap
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn cap_totals<C: Calories>(holdings: &mut [Holding<C>], cap: u128) -> Result<(), ElfError> {
+    while let Some((heaviest, lightest)) = heaviest_and_lightest(holdings) {
+        if holdings[heaviest].total <= cap {
+            return Ok(());
+        }
+        let room = cap - holdings[lightest].total;
+        // The biggest item that fits gets the heaviest elf under the cap in as few moves as possible
+        let best_item = holdings[heaviest]
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, item))| item.calories().into() <= room)
+            .max_by_key(|(_, (_, item))| item.calories())
+            .map(|(position, _)| position);
+        match best_item {
+            Some(position) => {
+                let item = holdings[heaviest].take(position);
+                holdings[lightest].give(item)?;
+            }
+            None => {
+                return Err(ElfError::UnableToRebalance(format!(
+                    "the greedy planner got stuck, no item carried by elf {} fits in the {} \
+                     calories left by elf {} (a plan may still exist)",
+                    heaviest, room, lightest
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Items get consecutive line numbers across the elves
+    fn elves(inventory: &[&[u32]]) -> Vec<Elf> {
+        let mut line = 1;
+        inventory
+            .iter()
+            .map(|items| {
+                let mut elf = Elf::new();
+                for calories in items.iter() {
+                    elf.add_calories(*calories, line);
+                    line += 1;
+                }
+                elf
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_even_is_minimal_for_small_inventories() {
+        let plan = plan_rebalance(&elves(&[&[3, 3, 2, 2, 2], &[0]]), RebalanceGoal::Even).unwrap();
+        assert!(plan.is_minimal());
+        assert_eq!(0, plan.spread());
+        // Both 3s move rather than the three 2s
+        assert_eq!(2, plan.transfers().len());
+        assert_eq!(&[6, 6], plan.totals());
+    }
+    #[test]
+    fn test_even_keeps_the_smallest_spread() {
+        let plan = plan_rebalance(&elves(&[&[10, 1, 1], &[5], &[5]]), RebalanceGoal::Even).unwrap();
+        assert_eq!(4, plan.spread());
+        assert_eq!(2, plan.transfers().len());
+        assert_eq!(&[10, 6, 6], plan.totals());
+    }
+    #[test]
+    fn test_cap_uses_the_fewest_transfers() {
+        let plan =
+            plan_rebalance(&elves(&[&[4, 3, 2, 1], &[], &[5]]), RebalanceGoal::Cap(5)).unwrap();
+        assert!(plan.is_minimal());
+        assert_eq!(2, plan.transfers().len());
+        assert!(plan.totals().iter().all(|total| *total <= 5));
+        let plan = plan_rebalance(&elves(&[&[4], &[5]]), RebalanceGoal::Cap(5)).unwrap();
+        assert_eq!(0, plan.transfers().len());
+    }
+    #[test]
+    fn test_cap_gives_up() {
+        // 12 calories fit in two elves of 6, but not as 4s
+        let error = plan_rebalance(&elves(&[&[4, 4, 4], &[]]), RebalanceGoal::Cap(6));
+        assert!(matches!(error, Err(ElfError::UnableToRebalance(_))));
+        let error = plan_rebalance(&elves(&[&[4, 4, 4], &[]]), RebalanceGoal::Cap(5));
+        assert!(matches!(error, Err(ElfError::UnableToRebalance(_))));
+        let error = plan_rebalance(&elves(&[&[7], &[]]), RebalanceGoal::Cap(6));
+        assert!(matches!(error, Err(ElfError::UnableToRebalance(_))));
+        // Too many items to search, the greedy planner gets stuck instead
+        let mut items = vec![4, 4, 4];
+        items.extend([0; 18]);
+        let error = plan_rebalance(&elves(&[&items, &[]]), RebalanceGoal::Cap(6));
+        assert!(matches!(error, Err(ElfError::UnableToRebalance(_))));
+    }
+    #[test]
+    fn test_greedy_for_big_inventories() {
+        let items = [1; 24];
+        let inventory = elves(&[&items, &[]]);
+        let plan = plan_rebalance(&inventory, RebalanceGoal::Even).unwrap();
+        assert!(!plan.is_minimal());
+        assert_eq!(0, plan.spread());
+        assert_eq!(12, plan.transfers().len());
+        let plan = plan_rebalance(&inventory, RebalanceGoal::Cap(20)).unwrap();
+        assert!(!plan.is_minimal());
+        assert_eq!(&[20, 4], plan.totals());
+    }
+    #[test]
+    fn test_widening_mul() {
+        assert_eq!((0, 6), widening_mul(2, 3));
+        assert_eq!((1, 0), widening_mul(1 << 64, 1 << 64));
+        assert_eq!((u128::MAX - 1, 1), widening_mul(u128::MAX, u128::MAX));
+        assert!(
+            widening_mul(u128::MAX / 2, u128::MAX / 2) > widening_mul(u128::MAX / 4, u128::MAX)
+        );
+    }
+}
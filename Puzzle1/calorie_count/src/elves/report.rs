@@ -0,0 +1,176 @@
+use super::calories::Calories;
+use super::ranking::RankedElf;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "Unknown format {}, expected text, json or csv",
+                format
+            )),
+        }
+    }
+}
+
+pub struct RankingReport<'a, C: Calories = u32> {
+    top_elves: &'a [RankedElf<C>],
+    top: usize,
+    include_ranking: bool,
+}
+
+impl<'a, C: Calories> RankingReport<'a, C> {
+    pub fn new(top_elves: &'a [RankedElf<C>], top: usize, include_ranking: bool) -> Self {
+        Self {
+            top_elves,
+            top,
+            include_ranking,
+        }
+    }
+    pub fn max_calories(&self) -> Option<C> {
+        self.top_elves.first().map(|elf| elf.calories())
+    }
+    // Only available when there are at least as many elves as requested and the sum fits in a u128
+    pub fn sum_top_calories(&self) -> Option<u128> {
+        if self.top_elves.len() < self.top {
+            return None;
+        }
+        self.top_elves
+            .iter()
+            .try_fold(0u128, |sum, elf| sum.checked_add(elf.calories().into()))
+    }
+    pub fn write<W: Write>(&self, format: OutputFormat, out: &mut W) -> io::Result<()> {
+        match format {
+            OutputFormat::Text => self.write_text(out),
+            OutputFormat::Json => self.write_json(out),
+            OutputFormat::Csv => self.write_csv(out),
+        }
+    }
+    fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if let Some(max_calories) = self.max_calories() {
+            writeln!(out, "Max calories: {}", max_calories)?;
+        }
+        if let Some(sum_calories) = self.sum_top_calories() {
+            writeln!(out, "Sum calories top {} elves: {}", self.top, sum_calories)?;
+        }
+        if self.include_ranking {
+            for (position, ranked_elf) in self.top_elves.iter().enumerate() {
+                write!(out, "{}. Elf {}", position + 1, ranked_elf.index())?;
+                if let Some(name) = ranked_elf.elf().name() {
+                    write!(out, " ({})", name)?;
+                }
+                writeln!(out, ": {} calories", ranked_elf.calories())?;
+            }
+        }
+        Ok(())
+    }
+    fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(
+            out,
+            "{{\"max_calories\":{},\"top\":{},\"sum_top_calories\":{}",
+            json_number(self.max_calories()),
+            self.top,
+            json_number(self.sum_top_calories())
+        )?;
+        if self.include_ranking {
+            write!(out, ",\"ranking\":[")?;
+            for (position, ranked_elf) in self.top_elves.iter().enumerate() {
+                if position > 0 {
+                    write!(out, ",")?;
+                }
+                write!(
+                    out,
+                    "{{\"rank\":{},\"elf\":{},\"name\":{},\"calories\":{}}}",
+                    position + 1,
+                    ranked_elf.index(),
+                    json_string(ranked_elf.elf().name()),
+                    ranked_elf.calories()
+                )?;
+            }
+            write!(out, "]")?;
+        }
+        writeln!(out, "}}")
+    }
+    fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if self.include_ranking {
+            writeln!(out, "rank,elf,name,calories")?;
+            for (position, ranked_elf) in self.top_elves.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{},{},{},{}",
+                    position + 1,
+                    ranked_elf.index(),
+                    csv_string(ranked_elf.elf().name()),
+                    ranked_elf.calories()
+                )?;
+            }
+            return Ok(());
+        }
+        writeln!(out, "max_calories,top,sum_top_calories")?;
+        writeln!(
+            out,
+            "{},{},{}",
+            csv_value(self.max_calories()),
+            self.top,
+            csv_value(self.sum_top_calories())
+        )
+    }
+}
+
+fn json_number<T: ToString>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("null"),
+    }
+}
+
+fn csv_value<T: ToString>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
+
+fn json_string(value: Option<&str>) -> String {
+    let value = match value {
+        Some(value) => value,
+        None => return String::from("null"),
+    };
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_string(value: Option<&str>) -> String {
+    match value {
+        Some(value) if value.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        }
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
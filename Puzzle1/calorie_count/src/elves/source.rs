@@ -0,0 +1,54 @@
+use std::io::{self, BufRead};
+
+// Reads one line at a time, keeping track of where we are so errors can point at it
+pub struct LineSource<R: BufRead> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    position: usize,
+}
+
+impl<R: BufRead> LineSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            position: 0,
+        }
+    }
+    // Returns false once there are no lines left
+    pub fn advance(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        self.position = 0;
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_number += 1;
+        let content_length = self.line.trim_end_matches(['\n', '\r']).len();
+        self.line.truncate(content_length);
+        Ok(true)
+    }
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+    // Starts at 1, 0 means nothing has been read yet
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+    // Part of the current line that hasn't been consumed
+    pub fn rest(&self) -> &str {
+        &self.line[self.position..]
+    }
+    // Byte offset in the current line
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    // Counts characters rather than bytes, so names like "Zoë" don't shift it
+    pub fn column(&self) -> usize {
+        self.line[..self.position].chars().count() + 1
+    }
+    pub fn consume(&mut self, bytes: usize) {
+        self.position = (self.position + bytes).min(self.line.len());
+    }
+}
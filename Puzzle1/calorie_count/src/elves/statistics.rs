@@ -0,0 +1,128 @@
+use super::calories::Calories;
+use super::elf::{Elf, FoodItem};
+
+pub struct Summary<C: Calories = u32> {
+    sorted_values: Vec<C>,
+    total: Option<u128>,
+}
+
+impl<C: Calories> Summary<C> {
+    pub fn from_values(mut values: Vec<C>) -> Self {
+        values.sort_unstable();
+        let total = values
+            .iter()
+            .try_fold(0u128, |total, value| total.checked_add((*value).into()));
+        Self {
+            sorted_values: values,
+            total,
+        }
+    }
+    pub fn count(&self) -> usize {
+        self.sorted_values.len()
+    }
+    // None if the total doesn't fit in a u128
+    pub fn total(&self) -> Option<u128> {
+        self.total
+    }
+    pub fn min(&self) -> Option<C> {
+        self.sorted_values.first().copied()
+    }
+    pub fn max(&self) -> Option<C> {
+        self.sorted_values.last().copied()
+    }
+    pub fn mean(&self) -> Option<f64> {
+        if self.sorted_values.is_empty() {
+            return None;
+        }
+        let total: f64 = self.sorted_values.iter().map(|value| as_f64(*value)).sum();
+        Some(total / self.count() as f64)
+    }
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+    // Linear interpolation between the closest ranks, percentile goes from 0 to 100
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        if self.sorted_values.is_empty() || !(0.0..=100.0).contains(&percentile) {
+            return None;
+        }
+        let rank = percentile / 100.0 * (self.count() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let lower_value = as_f64(self.sorted_values[lower]);
+        let upper_value = as_f64(self.sorted_values[upper]);
+        Some(lower_value + (upper_value - lower_value) * (rank - lower as f64))
+    }
+}
+
+fn as_f64<C: Calories>(value: C) -> f64 {
+    let value: u128 = value.into();
+    value as f64
+}
+
+pub struct ElfStatistics<C: Calories = u32> {
+    items: Summary<C>,
+    heaviest_item: Option<FoodItem<C>>,
+}
+
+impl<C: Calories> ElfStatistics<C> {
+    pub fn new(elf: &Elf<C>) -> Self {
+        let calories = elf.items().iter().map(|item| item.calories()).collect();
+        Self {
+            items: Summary::from_values(calories),
+            heaviest_item: elf.heaviest_item().copied(),
+        }
+    }
+    pub fn item_count(&self) -> usize {
+        self.items.count()
+    }
+    pub fn items(&self) -> &Summary<C> {
+        &self.items
+    }
+    pub fn heaviest_item(&self) -> Option<&FoodItem<C>> {
+        self.heaviest_item.as_ref()
+    }
+}
+
+// Can be fed while elves are streamed from the loader, but it keeps one total per elf for the
+// median and percentiles, so memory grows with the number of elves
+#[derive(Default)]
+pub struct GroupStatistics<C: Calories = u32> {
+    elf_totals: Vec<C>,
+    item_count: usize,
+    heaviest_item: Option<(usize, FoodItem<C>)>,
+}
+
+impl<C: Calories> GroupStatistics<C> {
+    pub fn new() -> Self {
+        Self {
+            elf_totals: vec![],
+            item_count: 0,
+            heaviest_item: None,
+        }
+    }
+    pub fn add_elf(&mut self, elf: &Elf<C>) {
+        let elf_index = self.elf_totals.len();
+        self.elf_totals.push(elf.calories());
+        self.item_count += elf.items().len();
+        if let Some(item) = elf.heaviest_item() {
+            match self.heaviest_item {
+                Some((_, heaviest)) if heaviest.calories() >= item.calories() => {}
+                _ => self.heaviest_item = Some((elf_index, *item)),
+            }
+        }
+    }
+    pub fn elf_count(&self) -> usize {
+        self.elf_totals.len()
+    }
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+    // Index of the elf carrying it and the item itself
+    pub fn heaviest_item(&self) -> Option<(usize, FoodItem<C>)> {
+        self.heaviest_item
+    }
+    // Sorts a copy of the totals, so keep the result around instead of calling this repeatedly
+    pub fn elf_totals(&self) -> Summary<C> {
+        Summary::from_values(self.elf_totals.clone())
+    }
+}
@@ -1,25 +1,311 @@
-use calorie_count::elves::loader;
-use clap::Parser;
+use calorie_count::elves::calories::{CalorieWidth, Calories};
+use calorie_count::elves::format::InventoryFormat;
+use calorie_count::elves::loader::{self, ElfReader, LoadMode};
+use calorie_count::elves::ranking::{self, Leaderboard};
+use calorie_count::elves::rebalance::{self, RebalanceGoal};
+use calorie_count::elves::report::{OutputFormat, RankingReport};
+use calorie_count::elves::statistics::{ElfStatistics, GroupStatistics, Summary};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use std::cell::RefCell;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::rc::Rc;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    // Reads from stdin when missing or set to "-"
     #[arg(short, long)]
-    filename: String,
+    filename: Option<String>,
+    #[arg(short, long, default_value_t = 3)]
+    top: usize,
+    // Lists every elf in the top with its index in the inventory
+    #[arg(action, short, long)]
+    rank: bool,
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
+    #[arg(action, short, long)]
+    stats: bool,
+    // Skips invalid lines and reports them instead of stopping at the first one
+    #[arg(action, short, long)]
+    lenient: bool,
+    // Integer type used to add up calories: u32, u64 or u128
+    #[arg(short, long, default_value = "u32")]
+    calories: CalorieWidth,
+    // Plans item transfers so every elf carries about the same calories, minimal for small
+    // inventories and greedy for the rest
+    #[arg(action, short = 'b', long)]
+    rebalance: bool,
+    // Plans item transfers so no elf carries more than this, implies --rebalance. Minimal for small
+    // inventories, greedy (it can miss plans that exist) for the rest
+    #[arg(long)]
+    cap: Option<u128>,
+    // blocks, csv or json, guessed from the file extension when missing (stdin defaults to blocks)
+    #[arg(short, long)]
+    input_format: Option<InventoryFormat>,
+    // Keeps reading as elves are appended to the file, stdin can't be followed. The top is printed
+    // once the existing elves are read, then whenever it changes. An elf only counts once a blank
+    // line, the next header or the next csv row closes its block, so a block still being written
+    // isn't shown yet
+    #[arg(
+        action,
+        long,
+        requires = "filename",
+        conflicts_with_all = ["stats", "rebalance", "cap"],
+        help = "Keep reading as elves are appended to the file and print the top whenever it \
+                changes. An elf only counts once a blank line, the next header or the next csv \
+                row closes its block"
+    )]
+    follow: bool,
+}
+
+fn format_optional(value: Option<f64>) -> String {
+    match value {
+        Some(value) => format!("{:.2}", value),
+        None => String::from("-"),
+    }
+}
+
+fn print_summary<C: Calories>(summary: &Summary<C>) {
+    println!(
+        "  mean: {}, median: {}, p90: {}, p99: {}",
+        format_optional(summary.mean()),
+        format_optional(summary.median()),
+        format_optional(summary.percentile(90.0)),
+        format_optional(summary.percentile(99.0))
+    );
 }
+
 fn main() {
     let args = Args::parse();
-    let mut elves = loader::load_elves_from_file(&args.filename).expect("Couldn't load elves");
-    elves.sort_by(|x, y| y.calories().cmp(&x.calories()));
-    if elves.len() > 0 {
-        println!("Max calories: {}", elves[0].calories());
-    }
-    if elves.len() >= 3 {
-        let num_elves = 3;
-        let mut sum_calories = 0;
-        for i in 0..num_elves {
-            sum_calories += elves[i].calories();
-        }
-        println!("Sum calories top 3 elves: {}", sum_calories);
+    if args.stats && args.format != OutputFormat::Text {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--stats is only available with the text format",
+            )
+            .exit();
+    }
+    if (args.rebalance || args.cap.is_some()) && args.format != OutputFormat::Text {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--rebalance and --cap are only available with the text format",
+            )
+            .exit();
+    }
+    if args.follow && args.filename.as_deref() == Some("-") {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--follow needs a file, it can't follow stdin",
+            )
+            .exit();
+    }
+    let mode = if args.lenient {
+        LoadMode::Lenient
+    } else {
+        LoadMode::Strict
+    };
+    match args.calories {
+        CalorieWidth::U32 => load_and_run::<u32>(&args, mode),
+        CalorieWidth::U64 => load_and_run::<u64>(&args, mode),
+        CalorieWidth::U128 => load_and_run::<u128>(&args, mode),
+    }
+}
+
+fn load_and_run<C: Calories + 'static>(args: &Args, mode: LoadMode) {
+    match args.filename.as_deref() {
+        None | Some("-") => {
+            let format = args.input_format.unwrap_or(InventoryFormat::Blocks);
+            run(loader::open_elves_stdin::<C>(format, mode), args)
+        }
+        Some(filename) if args.follow => {
+            let format = args
+                .input_format
+                .unwrap_or_else(|| InventoryFormat::from_path(filename));
+            run_follow::<C>(filename, format, mode, args)
+        }
+        Some(filename) => {
+            let format = args
+                .input_format
+                .unwrap_or_else(|| InventoryFormat::from_path(filename));
+            let elves_reader =
+                loader::open_elves_file::<C>(filename, format, mode).unwrap_or_else(|error| {
+                    eprintln!("{}", error);
+                    process::exit(1);
+                });
+            run(elves_reader, args)
+        }
+    }
+}
+
+fn report_diagnostics<R: BufRead, C: Calories>(elves_reader: &ElfReader<R, C>) {
+    let diagnostics = elves_reader.diagnostics();
+    if !diagnostics.is_empty() {
+        eprintln!("Skipped {} invalid lines:", diagnostics.len());
+        for error in diagnostics {
+            eprintln!("  {}", error);
+        }
+    }
+}
+
+fn print_leaderboard<C: Calories>(
+    leaderboard: &Leaderboard<C>,
+    top: usize,
+    include_ranking: bool,
+    format: OutputFormat,
+) {
+    let top_elves = leaderboard.ranking();
+    let mut out = io::stdout().lock();
+    RankingReport::new(&top_elves, top, include_ranking)
+        .write(format, &mut out)
+        .expect("Couldn't write report");
+    if format == OutputFormat::Text {
+        writeln!(out, "({} elves read)", leaderboard.elf_count()).expect("Couldn't write report");
+    }
+}
+
+struct FollowState<C: Calories> {
+    leaderboard: Leaderboard<C>,
+    caught_up: bool,
+}
+
+// The elves already in the file are ranked quietly and the top is printed once when the end of
+// the file is reached, after that it's printed whenever an appended elf changes it
+fn run_follow<C: Calories + 'static>(
+    filename: &str,
+    format: InventoryFormat,
+    mode: LoadMode,
+    args: &Args,
+) {
+    let state = Rc::new(RefCell::new(FollowState {
+        leaderboard: Leaderboard::<C>::new(args.top),
+        caught_up: false,
+    }));
+    let on_caught_up = {
+        let state = Rc::clone(&state);
+        let (top, include_ranking, output_format) = (args.top, args.rank, args.format);
+        move || {
+            let mut state = state.borrow_mut();
+            state.caught_up = true;
+            print_leaderboard(&state.leaderboard, top, include_ranking, output_format);
+        }
+    };
+    let mut elves_reader = loader::follow_elves_file::<C, _>(filename, format, mode, on_caught_up)
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+    let mut reported_diagnostics = 0;
+    while let Some(elf) = elves_reader.next() {
+        for error in elves_reader.diagnostics()[reported_diagnostics..].iter() {
+            eprintln!("Skipped: {}", error);
+        }
+        reported_diagnostics = elves_reader.diagnostics().len();
+        let elf = elf.unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let mut state = state.borrow_mut();
+        if state.leaderboard.push(elf) && state.caught_up {
+            print_leaderboard(&state.leaderboard, args.top, args.rank, args.format);
+        }
+    }
+}
+
+fn run_rebalance<R: BufRead, C: Calories>(mut elves_reader: ElfReader<R, C>, args: &Args) {
+    let goal = match args.cap {
+        Some(cap) => RebalanceGoal::Cap(cap),
+        None => RebalanceGoal::Even,
+    };
+    let elves = elves_reader.by_ref().collect::<Result<Vec<_>, _>>();
+    report_diagnostics(&elves_reader);
+    let elves = elves.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let plan = rebalance::plan_rebalance(&elves, goal).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let kind = if plan.is_minimal() {
+        "minimal plan"
+    } else {
+        "greedy plan, not guaranteed minimal"
+    };
+    println!("Transfers: {} ({})", plan.transfers().len(), kind);
+    for transfer in plan.transfers() {
+        println!(
+            "Move {} calories (line {}) from elf {} to elf {}",
+            transfer.item().calories(),
+            transfer.item().line(),
+            transfer.from(),
+            transfer.to()
+        );
+    }
+    for (elf_index, total) in plan.totals().iter().enumerate() {
+        println!("Elf {}: {} calories", elf_index, total);
+    }
+    println!("Spread: {}", plan.spread());
+}
+
+fn run<R: BufRead, C: Calories>(mut elves_reader: ElfReader<R, C>, args: &Args) {
+    if args.rebalance || args.cap.is_some() {
+        return run_rebalance(elves_reader, args);
+    }
+    let num_elves = args.top;
+    // Only collected with --stats, it keeps every elf's total while the ranking keeps just the top
+    let mut group_statistics = args.stats.then(GroupStatistics::new);
+    let top_elves = ranking::top_n(
+        elves_reader.by_ref().inspect(|elf| {
+            if let (Some(group_statistics), Ok(elf)) = (group_statistics.as_mut(), elf) {
+                group_statistics.add_elf(elf);
+            }
+        }),
+        num_elves,
+    );
+    report_diagnostics(&elves_reader);
+    let top_elves = top_elves.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    RankingReport::new(&top_elves, num_elves, args.rank)
+        .write(args.format, &mut io::stdout().lock())
+        .expect("Couldn't write report");
+    let Some(group_statistics) = group_statistics else {
+        return;
+    };
+    println!(
+        "Group: {} elves carrying {} items",
+        group_statistics.elf_count(),
+        group_statistics.item_count()
+    );
+    print_summary(&group_statistics.elf_totals());
+    if let Some((elf_index, item)) = group_statistics.heaviest_item() {
+        println!(
+            "  heaviest item: {} calories (elf {}, line {})",
+            item.calories(),
+            elf_index,
+            item.line()
+        );
+    }
+    for ranked_elf in top_elves.iter() {
+        let elf_statistics = ElfStatistics::new(ranked_elf.elf());
+        println!(
+            "Elf {}: {} calories in {} items",
+            ranked_elf.index(),
+            ranked_elf.calories(),
+            elf_statistics.item_count()
+        );
+        print_summary(elf_statistics.items());
+        if let Some(item) = elf_statistics.heaviest_item() {
+            println!(
+                "  heaviest item: {} calories (line {})",
+                item.calories(),
+                item.line()
+            );
+        }
     }
 }
@@ -0,0 +1,120 @@
+use clap::Parser;
+use rock_paper_scissors::game::game::LoadMode;
+use rock_paper_scissors::game::optimizer;
+use rock_paper_scissors::game::player::StrategyInterpretation;
+use rock_paper_scissors::game::scoring::ScoringProfile;
+use rock_paper_scissors::game::session::{OpponentSource, PlaySession};
+use rock_paper_scissors::game::tournament::StrategySpec;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about = "Play rock paper scissors from the terminal", long_about = None)]
+struct Args {
+    // fixed:<moves>, frequency, markov or random:<seed>
+    #[arg(short, long, conflicts_with = "guide")]
+    bot: Option<StrategySpec>,
+    // Plays against the opponent column of this strategy guide
+    #[arg(short, long)]
+    guide: Option<String>,
+    // move (X/Y/Z are Rock/Paper/Scissors) or outcome (X/Y/Z are lose/draw/win)
+    #[arg(short, long, default_value = "move")]
+    interpretation: StrategyInterpretation,
+    // A preset (standard, wins-only, league) or a scoring profile file
+    #[arg(short, long, default_value = "standard")]
+    scoring: String,
+}
+
+const HELP: &str = "Type X, Y or Z to play (A Y with no bot or guide), undo to take back the last round, score to see the total and quit to leave";
+
+fn exit_with<E: Display>(error: E) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
+}
+
+fn prompt(session: &PlaySession) {
+    match session.announced_opponent() {
+        Some(opponent) => print!(
+            "Round {}, opponent plays {} > ",
+            session.rounds().len() + 1,
+            opponent.name()
+        ),
+        None => print!("Round {} > ", session.rounds().len() + 1),
+    }
+    io::stdout().flush().expect("Couldn't write prompt");
+}
+
+fn main() {
+    let args = Args::parse();
+    let profile =
+        ScoringProfile::from_preset_or_file(&args.scoring).unwrap_or_else(|e| exit_with(e));
+    let source = match (&args.bot, &args.guide) {
+        (Some(spec), _) => OpponentSource::Bot(spec.clone()),
+        (None, Some(guide_filename)) => {
+            let guide_file = File::open(guide_filename)
+                .unwrap_or_else(|e| exit_with(format!("Couldn't open {}: {}", guide_filename, e)));
+            let (guide, _) = optimizer::load_guide(BufReader::new(guide_file), LoadMode::Strict)
+                .unwrap_or_else(|e| exit_with(e));
+            OpponentSource::Guide(guide.iter().map(|round| round.opponent()).collect())
+        }
+        (None, None) => OpponentSource::Manual,
+    };
+    let mut session = PlaySession::new(source, args.interpretation, profile);
+    println!("{}", HELP);
+    prompt(&session);
+    for line in io::stdin().lock().lines() {
+        let line = line.expect("Couldn't read input");
+        match line.trim() {
+            "" => {}
+            "quit" | "exit" => break,
+            "help" => println!("{}", HELP),
+            "score" => println!(
+                "Total: {} points in {} rounds",
+                session.score(),
+                session.rounds().len()
+            ),
+            "undo" => match session.undo() {
+                Some(round) => println!(
+                    "Took back {} vs {} ({} points), total {}",
+                    round.player().hand().name(),
+                    round.opponent().name(),
+                    round.round_points(),
+                    session.score()
+                ),
+                None => println!("Nothing to undo"),
+            },
+            input => match session.play(input) {
+                Ok(round_points) => {
+                    let round = session.rounds().last().expect("A round was just played");
+                    let hand = round.player().hand();
+                    let outcome = round.outcome();
+                    let shape_points = session.profile().hand_points(hand);
+                    let outcome_points = session.profile().outcome_points(outcome);
+                    println!(
+                        "You played {} against {}: {} ({} + {} = {} points), total {}",
+                        hand.name(),
+                        round.opponent().name(),
+                        outcome.name(),
+                        shape_points,
+                        outcome_points,
+                        round_points,
+                        session.score()
+                    );
+                }
+                Err(error) => println!("{}", error),
+            },
+        }
+        if session.is_over() {
+            println!("The guide is over");
+            break;
+        }
+        prompt(&session);
+    }
+    println!(
+        "Final score: {} points in {} rounds",
+        session.score(),
+        session.rounds().len()
+    );
+}
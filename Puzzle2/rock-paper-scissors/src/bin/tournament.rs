@@ -0,0 +1,81 @@
+use clap::Parser;
+use rock_paper_scissors::game::hand_option::HandOption;
+use rock_paper_scissors::game::scoring::ScoringProfile;
+use rock_paper_scissors::game::tournament::{StrategySpec, Tournament};
+use std::fmt::Display;
+use std::process;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about = "Round robin tournament between rock paper scissors bots", long_about = None)]
+struct Args {
+    // fixed:<moves> (like fixed:RRPS), frequency, markov or random:<seed>, can be repeated
+    #[arg(short = 'p', long = "strategy")]
+    strategies: Vec<StrategySpec>,
+    // Rounds played in every match
+    #[arg(short, long, default_value_t = 1000)]
+    rounds: usize,
+    // A preset (standard, wins-only, league) or a scoring profile file
+    #[arg(short, long, default_value = "standard")]
+    scoring: String,
+    // Prints every strategy's cumulative score every this many rounds
+    #[arg(short, long)]
+    timeline: Option<usize>,
+}
+
+fn exit_with<E: Display>(error: E) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
+}
+
+fn main() {
+    let args = Args::parse();
+    let strategies = if args.strategies.is_empty() {
+        vec![
+            StrategySpec::Fixed(vec![HandOption::Rock]),
+            StrategySpec::Frequency,
+            StrategySpec::Markov,
+            StrategySpec::Random(0),
+        ]
+    } else {
+        args.strategies.clone()
+    };
+    let profile =
+        ScoringProfile::from_preset_or_file(&args.scoring).unwrap_or_else(|e| exit_with(e));
+    let tournament = Tournament::round_robin(strategies, args.rounds, &profile);
+    for result in tournament.matches() {
+        println!(
+            "{} vs {}: {} - {}",
+            tournament.strategies()[result.first()].name(),
+            tournament.strategies()[result.second()].name(),
+            result.first_score(),
+            result.second_score()
+        );
+    }
+    println!("Standings:");
+    for (position, (strategy, standing)) in tournament.standings().iter().enumerate() {
+        println!(
+            "{}. {}: {} points ({}W {}D {}L)",
+            position + 1,
+            tournament.strategies()[*strategy].name(),
+            standing.points(),
+            standing.match_wins(),
+            standing.match_draws(),
+            standing.match_losses()
+        );
+    }
+    let step = match args.timeline {
+        Some(step) if step > 0 => step,
+        _ => return,
+    };
+    println!("Timelines:");
+    for (strategy, spec) in tournament.strategies().iter().enumerate() {
+        let samples = tournament
+            .timeline(strategy)
+            .iter()
+            .skip(step - 1)
+            .step_by(step)
+            .map(|score| score.to_string())
+            .collect::<Vec<_>>();
+        println!("{}: {}", spec.name(), samples.join(" "));
+    }
+}
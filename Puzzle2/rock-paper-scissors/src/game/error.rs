@@ -5,6 +5,15 @@ use std::fmt;
 pub enum RockPaperScissorsError {
     InvalidMove(String),
     InvalidGame(String),
+    InvalidRules(String),
+    InvalidScoring(String),
+    // line and column start at 1
+    InvalidLine {
+        line: usize,
+        column: usize,
+        token: String,
+        reason: String,
+    },
 }
 
 impl From<String> for RockPaperScissorsError {
@@ -17,6 +26,18 @@ impl fmt::Display for RockPaperScissorsError {
         match self {
             Self::InvalidMove(e) => write!(f, "Invalid move: {}", e),
             Self::InvalidGame(e) => write!(f, "Invalid game: {}", e),
+            Self::InvalidRules(e) => write!(f, "Invalid rules: {}", e),
+            Self::InvalidScoring(e) => write!(f, "Invalid scoring profile: {}", e),
+            Self::InvalidLine {
+                line,
+                column,
+                token,
+                reason,
+            } => write!(
+                f,
+                "Invalid round at line {}, column {}: \"{}\" ({})",
+                line, column, token, reason
+            ),
         }
     }
 }
@@ -1,59 +1,234 @@
 use super::error::RockPaperScissorsError;
 use super::hand_option::HandOption;
 use super::opponent::parse_opponent;
-use super::player::{parse_player_move, PlayerOption};
-use std::fs;
+use super::player::{parse_player_move, PlayerOption, StrategyInterpretation};
+use super::rules::{Outcome, RuleTable};
+use super::scoring::ScoringProfile;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
 pub struct Round {
     opponent: HandOption,
     player: PlayerOption,
-    round_points: u32,
+    round_points: u64,
 }
 
 impl Round {
-    pub fn new(opponent_str: &str, player_str: &str) -> Result<Self, RockPaperScissorsError> {
+    pub fn new(
+        opponent_str: &str,
+        player_str: &str,
+        interpretation: StrategyInterpretation,
+        profile: &ScoringProfile,
+    ) -> Result<Self, RockPaperScissorsError> {
         let opponent = parse_opponent(opponent_str)?;
-        let player = parse_player_move(opponent, player_str)?;
+        let player = parse_player_move(opponent, player_str, interpretation)?;
         Ok(Self {
             opponent,
             player,
-            round_points: resolve_round_points(opponent, player),
+            round_points: resolve_round_points(opponent, player, profile),
         })
     }
+    // For rounds that are played instead of read from a guide
+    pub fn from_moves(opponent: HandOption, player: HandOption, profile: &ScoringProfile) -> Self {
+        let player = PlayerOption::new(player);
+        Self {
+            opponent,
+            player,
+            round_points: resolve_round_points(opponent, player, profile),
+        }
+    }
     pub fn opponent(&self) -> HandOption {
         self.opponent
     }
     pub fn player(&self) -> PlayerOption {
         self.player
     }
-    pub fn round_points(&self) -> u32 {
+    pub fn round_points(&self) -> u64 {
         self.round_points
     }
+    pub fn outcome(&self) -> Outcome {
+        self.player.hand().outcome_against(self.opponent)
+    }
+}
+
+// Both the shape and the outcome points come from the profile, ScoringProfile::standard() gives
+// the same points PlayerOption::new keeps
+pub fn resolve_round_points(
+    opponent_move: HandOption,
+    player_move: PlayerOption,
+    profile: &ScoringProfile,
+) -> u64 {
+    let hand = player_move.hand();
+    u64::from(profile.hand_points(hand))
+        + u64::from(profile.outcome_points(hand.outcome_against(opponent_move)))
 }
 
-pub fn resolve_round_points(opponent_move: HandOption, player_move: PlayerOption) -> u32 {
-    match player_move {
-        PlayerOption::Rock(hand, points)
-        | PlayerOption::Paper(hand, points)
-        | PlayerOption::Scissors(hand, points) => points + hand.points_against(opponent_move),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    // Stops at the first invalid line
+    Strict,
+    // Skips invalid lines and returns their errors along with everything else
+    Lenient,
+}
+
+// A word of a guide line along with the column (starting at 1) where it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    text: &'a str,
+    column: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+    pub fn column(&self) -> usize {
+        self.column
+    }
+    // Points the error at this token
+    pub fn error(&self, line: usize, error: RockPaperScissorsError) -> RockPaperScissorsError {
+        RockPaperScissorsError::InvalidLine {
+            line,
+            column: self.column,
+            token: self.text.to_string(),
+            reason: error.to_string(),
+        }
     }
 }
 
-pub fn load_game(filename: &str) -> Result<Vec<Round>, RockPaperScissorsError> {
-    let mut rounds = vec![];
-    let game_str = fs::read_to_string(filename)?;
-    for game_line in game_str.lines() {
-        if game_line.trim().is_empty() {
-            continue;
+fn tokens(game_line: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (column, (position, c)) in game_line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((position, column + 1)),
+            (true, Some((start_position, start_column))) => {
+                tokens.push(Token {
+                    text: &game_line[start_position..position],
+                    column: start_column,
+                });
+                start = None;
+            }
+            _ => {}
         }
-        let round_line = game_line.trim().split_whitespace().collect::<Vec<_>>();
-        if round_line.len() < 2 {
-            return Err(RockPaperScissorsError::InvalidGame(format!(
-                "Game is missing players: {}",
-                game_line
-            )));
+    }
+    if let Some((start_position, start_column)) = start {
+        tokens.push(Token {
+            text: &game_line[start_position..],
+            column: start_column,
+        });
+    }
+    tokens
+}
+
+// The first two columns of a guide line, None for blank lines
+pub fn parse_round_line(
+    line: usize,
+    game_line: &str,
+) -> Result<Option<(Token<'_>, Token<'_>)>, RockPaperScissorsError> {
+    let round_line = tokens(game_line);
+    match round_line.as_slice() {
+        [] => Ok(None),
+        [opponent] => Err(RockPaperScissorsError::InvalidLine {
+            line,
+            column: opponent.column + opponent.text.chars().count(),
+            token: String::new(),
+            reason: String::from("Game is missing players"),
+        }),
+        [opponent, player, ..] => Ok(Some((*opponent, *player))),
+    }
+}
+
+// Calls parse_round with the line number and the two columns of every round in the guide.
+// Reading errors always stop, invalid rounds only do in strict mode, otherwise they are returned
+pub fn read_guide<R, F>(
+    reader: R,
+    mode: LoadMode,
+    mut parse_round: F,
+) -> Result<Vec<RockPaperScissorsError>, RockPaperScissorsError>
+where
+    R: BufRead,
+    F: FnMut(usize, Token, Token) -> Result<(), RockPaperScissorsError>,
+{
+    let mut skipped = vec![];
+    for (line_index, game_line) in reader.lines().enumerate() {
+        let game_line = game_line?;
+        let result = match parse_round_line(line_index + 1, &game_line) {
+            Ok(Some((opponent, player))) => parse_round(line_index + 1, opponent, player),
+            Ok(None) => Ok(()),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            match mode {
+                LoadMode::Strict => return Err(error),
+                LoadMode::Lenient => skipped.push(error),
+            }
         }
-        rounds.push(Round::new(round_line[0], round_line[1])?);
     }
+    Ok(skipped)
+}
+
+pub fn load_game(
+    filename: &str,
+    interpretation: StrategyInterpretation,
+    profile: &ScoringProfile,
+) -> Result<Vec<Round>, RockPaperScissorsError> {
+    let game_file = File::open(filename)?;
+    let (rounds, _) = load_game_from(
+        BufReader::new(game_file),
+        interpretation,
+        profile,
+        LoadMode::Strict,
+    )?;
     Ok(rounds)
 }
+
+// Returns the rounds along with the errors of the lines skipped in lenient mode
+pub fn load_game_from<R: BufRead>(
+    reader: R,
+    interpretation: StrategyInterpretation,
+    profile: &ScoringProfile,
+    mode: LoadMode,
+) -> Result<(Vec<Round>, Vec<RockPaperScissorsError>), RockPaperScissorsError> {
+    let mut rounds = vec![];
+    let skipped = read_guide(reader, mode, |line, opponent_token, player_token| {
+        let opponent =
+            parse_opponent(opponent_token.text()).map_err(|e| opponent_token.error(line, e))?;
+        let player = parse_player_move(opponent, player_token.text(), interpretation)
+            .map_err(|e| player_token.error(line, e))?;
+        rounds.push(Round::from_moves(opponent, player.hand(), profile));
+        Ok(())
+    })?;
+    Ok((rounds, skipped))
+}
+
+// Scores the guide under every interpretation while going through it once,
+// the totals are in the same order as the interpretations. Use RuleTable::classic() for the usual game
+pub fn load_game_totals<R: BufRead>(
+    reader: R,
+    rules: &RuleTable,
+    profile: &ScoringProfile,
+    interpretations: &[StrategyInterpretation],
+    mode: LoadMode,
+) -> Result<(Vec<u64>, Vec<RockPaperScissorsError>), RockPaperScissorsError> {
+    let mut totals = vec![0; interpretations.len()];
+    let skipped = read_guide(reader, mode, |line, opponent_token, player_token| {
+        let opponent = rules
+            .parse_opponent(opponent_token.text())
+            .map_err(|e| opponent_token.error(line, e))?;
+        // A round only counts if it's valid under every interpretation
+        let players = interpretations
+            .iter()
+            .map(|interpretation| {
+                rules
+                    .parse_player_move(opponent, player_token.text(), *interpretation)
+                    .map_err(|e| player_token.error(line, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (total, player) in totals.iter_mut().zip(players) {
+            *total += rules.round_points(player, opponent, profile);
+        }
+        Ok(())
+    })?;
+    Ok((totals, skipped))
+}
@@ -1,4 +1,7 @@
-#[derive(Debug, Clone, Copy)]
+use super::rules::{Outcome, RuleTable};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HandOption {
     Rock,
     Paper,
@@ -6,35 +9,58 @@ pub enum HandOption {
 }
 
 impl HandOption {
-    pub fn points_against(&self, opponent: HandOption) -> u32 {
+    pub fn all() -> [HandOption; 3] {
+        [Self::Rock, Self::Paper, Self::Scissors]
+    }
+
+    // Position of the move in the classic rule table
+    pub fn move_index(&self) -> usize {
         match self {
-            Self::Rock => self.resolve_rock(opponent),
-            Self::Paper => self.resolve_paper(opponent),
-            Self::Scissors => self.resolve_scissors(opponent),
+            Self::Rock => 0,
+            Self::Paper => 1,
+            Self::Scissors => 2,
         }
     }
 
-    fn resolve_rock(&self, opponent: HandOption) -> u32 {
-        match opponent {
-            Self::Rock => 3,
-            Self::Paper => 0,
-            Self::Scissors => 6,
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rock => "Rock",
+            Self::Paper => "Paper",
+            Self::Scissors => "Scissors",
         }
     }
 
-    fn resolve_paper(&self, opponent: HandOption) -> u32 {
-        match opponent {
-            Self::Rock => 6,
-            Self::Paper => 3,
-            Self::Scissors => 0,
-        }
+    pub fn from_move_index(move_index: usize) -> Option<HandOption> {
+        Self::all().get(move_index).copied()
     }
 
-    fn resolve_scissors(&self, opponent: HandOption) -> u32 {
-        match opponent {
-            Self::Rock => 0,
-            Self::Paper => 6,
-            Self::Scissors => 3,
+    pub fn outcome_against(&self, opponent: HandOption) -> Outcome {
+        RuleTable::classic().outcome(self.move_index(), opponent.move_index())
+    }
+
+    // The move that beats this one
+    pub fn counter(&self) -> HandOption {
+        let move_index = RuleTable::classic()
+            .move_for_outcome(self.move_index(), Outcome::Win)
+            .expect("Every classic move can be beaten");
+        Self::from_move_index(move_index).expect("Classic moves are hand options")
+    }
+
+    pub fn points_against(&self, opponent: HandOption) -> u32 {
+        self.outcome_against(opponent).points()
+    }
+}
+
+// Accepts the full name or its first letter, in any case
+impl FromStr for HandOption {
+    type Err = String;
+
+    fn from_str(hand: &str) -> Result<Self, Self::Err> {
+        match hand.to_lowercase().as_str() {
+            "r" | "rock" => Ok(Self::Rock),
+            "p" | "paper" => Ok(Self::Paper),
+            "s" | "scissors" => Ok(Self::Scissors),
+            _ => Err(format!("No such move: {}", hand)),
         }
     }
 }
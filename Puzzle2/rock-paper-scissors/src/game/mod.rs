@@ -1,5 +1,12 @@
 pub mod error;
+#[allow(clippy::module_inception)]
 pub mod game;
 pub mod hand_option;
 pub mod opponent;
+pub mod optimizer;
 pub mod player;
+pub mod report;
+pub mod rules;
+pub mod scoring;
+pub mod session;
+pub mod tournament;
@@ -0,0 +1,216 @@
+use super::error::RockPaperScissorsError;
+use super::game::{read_guide, LoadMode};
+use super::hand_option::HandOption;
+use super::opponent::parse_opponent;
+use super::player::{PlayerOption, StrategyInterpretation};
+use super::rules::{Outcome, RuleTable};
+use super::scoring::ScoringProfile;
+use std::fmt;
+use std::io::BufRead;
+
+const SYMBOLS: [&str; 3] = ["X", "Y", "Z"];
+
+// A round of the guide before deciding what its second column means
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuideRound {
+    line: usize,
+    opponent: HandOption,
+    // 0, 1 and 2 for X, Y and Z
+    symbol: usize,
+}
+
+impl GuideRound {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    pub fn opponent(&self) -> HandOption {
+        self.opponent
+    }
+    pub fn symbol(&self) -> &'static str {
+        SYMBOLS[self.symbol]
+    }
+}
+
+// Returns the rounds along with the errors of the lines skipped in lenient mode
+pub fn load_guide<R: BufRead>(
+    reader: R,
+    mode: LoadMode,
+) -> Result<(Vec<GuideRound>, Vec<RockPaperScissorsError>), RockPaperScissorsError> {
+    let mut guide = vec![];
+    let skipped = read_guide(reader, mode, |line, opponent_token, player_token| {
+        let opponent =
+            parse_opponent(opponent_token.text()).map_err(|e| opponent_token.error(line, e))?;
+        let symbol = SYMBOLS
+            .iter()
+            .position(|symbol| *symbol == player_token.text())
+            .ok_or_else(|| {
+                player_token.error(
+                    line,
+                    RockPaperScissorsError::from(format!("No such move: {}", player_token.text())),
+                )
+            })?;
+        guide.push(GuideRound {
+            line,
+            opponent,
+            symbol,
+        });
+        Ok(())
+    })?;
+    Ok((guide, skipped))
+}
+
+// What X, Y and Z stand for, in that order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mapping {
+    Moves([HandOption; 3]),
+    Outcomes([Outcome; 3]),
+}
+
+fn permutations<T: Copy>(values: [T; 3]) -> Vec<[T; 3]> {
+    let [a, b, c] = values;
+    vec![
+        [a, b, c],
+        [a, c, b],
+        [b, a, c],
+        [b, c, a],
+        [c, a, b],
+        [c, b, a],
+    ]
+}
+
+impl Mapping {
+    // Every bijection from X/Y/Z to moves, then every one to outcomes
+    pub fn all() -> Vec<Mapping> {
+        let moves = permutations(HandOption::all()).into_iter().map(Self::Moves);
+        let outcomes = permutations([Outcome::Loss, Outcome::Draw, Outcome::Win])
+            .into_iter()
+            .map(Self::Outcomes);
+        moves.chain(outcomes).collect()
+    }
+
+    pub fn from_interpretation(interpretation: StrategyInterpretation) -> Self {
+        match interpretation {
+            StrategyInterpretation::Move => Self::Moves(HandOption::all()),
+            StrategyInterpretation::Outcome => {
+                Self::Outcomes([Outcome::Loss, Outcome::Draw, Outcome::Win])
+            }
+        }
+    }
+
+    pub fn player_move(&self, round: &GuideRound) -> HandOption {
+        match self {
+            Self::Moves(moves) => moves[round.symbol],
+            Self::Outcomes(outcomes) => {
+                let move_index = RuleTable::classic()
+                    .move_for_outcome(round.opponent.move_index(), outcomes[round.symbol])
+                    .expect("Every outcome is possible in the classic game");
+                HandOption::from_move_index(move_index).expect("Classic moves are hand options")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, symbol) in SYMBOLS.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            match self {
+                Self::Moves(moves) => write!(f, "{}={}", symbol, moves[index].name())?,
+                Self::Outcomes(outcomes) => write!(f, "{}={:?}", symbol, outcomes[index])?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct MappingScore {
+    mapping: Mapping,
+    total: u64,
+    // One per round of the guide
+    outcomes: Vec<Outcome>,
+}
+
+impl MappingScore {
+    pub fn mapping(&self) -> Mapping {
+        self.mapping
+    }
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+    pub fn outcomes(&self) -> &[Outcome] {
+        &self.outcomes
+    }
+}
+
+pub struct MappingAnalysis {
+    guide: Vec<GuideRound>,
+    scores: Vec<MappingScore>,
+}
+
+impl MappingAnalysis {
+    pub fn new(guide: Vec<GuideRound>, profile: &ScoringProfile) -> Self {
+        let scores = Mapping::all()
+            .into_iter()
+            .map(|mapping| {
+                let mut total = 0;
+                let mut outcomes = vec![];
+                for round in guide.iter() {
+                    let player = PlayerOption::new(mapping.player_move(round));
+                    total += super::game::resolve_round_points(round.opponent, player, profile);
+                    outcomes.push(player.hand().outcome_against(round.opponent));
+                }
+                MappingScore {
+                    mapping,
+                    total,
+                    outcomes,
+                }
+            })
+            .collect();
+        Self { guide, scores }
+    }
+    pub fn guide(&self) -> &[GuideRound] {
+        &self.guide
+    }
+    // Same order as Mapping::all()
+    pub fn scores(&self) -> &[MappingScore] {
+        &self.scores
+    }
+    pub fn score(&self, mapping: Mapping) -> Option<&MappingScore> {
+        self.scores.iter().find(|score| score.mapping == mapping)
+    }
+    // On ties the first mapping wins
+    pub fn best(&self) -> Option<&MappingScore> {
+        self.scores.iter().reduce(|best, score| {
+            if score.total > best.total {
+                score
+            } else {
+                best
+            }
+        })
+    }
+    // Rounds whose outcome isn't the same under both mappings
+    pub fn changed_rounds(&self, first: &MappingScore, second: &MappingScore) -> Vec<GuideRound> {
+        self.guide
+            .iter()
+            .zip(first.outcomes.iter().zip(second.outcomes.iter()))
+            .filter(|(_, (first, second))| first != second)
+            .map(|(round, _)| *round)
+            .collect()
+    }
+    // Rounds that end up differently depending on the mapping
+    pub fn contested_rounds(&self) -> Vec<GuideRound> {
+        self.guide
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                let first = self.scores[0].outcomes[*index];
+                self.scores
+                    .iter()
+                    .any(|score| score.outcomes[*index] != first)
+            })
+            .map(|(_, round)| *round)
+            .collect()
+    }
+}
@@ -1,6 +1,44 @@
 use super::error::RockPaperScissorsError;
 use super::hand_option::HandOption;
 use std::convert::From;
+use std::str::FromStr;
+
+// How the second column of the strategy guide (X/Y/Z) should be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyInterpretation {
+    // X/Y/Z are Rock/Paper/Scissors
+    Move,
+    // X/Y/Z mean we have to lose/draw/win
+    #[default]
+    Outcome,
+}
+
+impl StrategyInterpretation {
+    pub fn all() -> [StrategyInterpretation; 2] {
+        [Self::Move, Self::Outcome]
+    }
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Move => "X/Y/Z as moves",
+            Self::Outcome => "X/Y/Z as outcomes",
+        }
+    }
+}
+
+impl FromStr for StrategyInterpretation {
+    type Err = String;
+
+    fn from_str(interpretation: &str) -> Result<Self, Self::Err> {
+        match interpretation.to_lowercase().as_str() {
+            "move" | "moves" => Ok(Self::Move),
+            "outcome" | "outcomes" => Ok(Self::Outcome),
+            _ => Err(format!(
+                "Unknown interpretation {}, expected move or outcome",
+                interpretation
+            )),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub enum PlayerOption {
@@ -16,16 +54,25 @@ impl PlayerOption {
             HandOption::Scissors => Self::Scissors(HandOption::Scissors, 3),
         }
     }
+    pub fn hand(&self) -> HandOption {
+        match self {
+            Self::Rock(hand, _) | Self::Paper(hand, _) | Self::Scissors(hand, _) => *hand,
+        }
+    }
 }
 
 pub fn parse_player_move(
     opponent_move: HandOption,
     player_move: &str,
+    interpretation: StrategyInterpretation,
 ) -> Result<PlayerOption, RockPaperScissorsError> {
-    let chosen_move = match player_move {
-        "X" => parse_lose(opponent_move),
-        "Y" => parse_draw(opponent_move),
-        "Z" => parse_win(opponent_move),
+    let chosen_move = match (interpretation, player_move) {
+        (StrategyInterpretation::Move, "X") => HandOption::Rock,
+        (StrategyInterpretation::Move, "Y") => HandOption::Paper,
+        (StrategyInterpretation::Move, "Z") => HandOption::Scissors,
+        (StrategyInterpretation::Outcome, "X") => parse_lose(opponent_move),
+        (StrategyInterpretation::Outcome, "Y") => parse_draw(opponent_move),
+        (StrategyInterpretation::Outcome, "Z") => parse_win(opponent_move),
         _ => {
             return Err(RockPaperScissorsError::from(format!(
                 "No such move: {}",
@@ -0,0 +1,235 @@
+use super::game::Round;
+use super::hand_option::HandOption;
+use super::rules::Outcome;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown format {}, expected text, csv or json",
+                format
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MoveSummary {
+    played: u32,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    points: u64,
+}
+
+impl MoveSummary {
+    pub fn played(&self) -> u32 {
+        self.played
+    }
+    pub fn count(&self, outcome: Outcome) -> u32 {
+        match outcome {
+            Outcome::Win => self.wins,
+            Outcome::Draw => self.draws,
+            Outcome::Loss => self.losses,
+        }
+    }
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+    fn add(&mut self, round: &Round) {
+        self.played += 1;
+        self.points += round.round_points();
+        match round.outcome() {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+}
+
+pub struct GameReport<'a> {
+    rounds: &'a [Round],
+    // Indexed by HandOption::move_index
+    moves: [MoveSummary; 3],
+    total: MoveSummary,
+}
+
+impl<'a> GameReport<'a> {
+    pub fn new(rounds: &'a [Round]) -> Self {
+        let mut moves = [MoveSummary::default(); 3];
+        let mut total = MoveSummary::default();
+        for round in rounds {
+            moves[round.player().hand().move_index()].add(round);
+            total.add(round);
+        }
+        Self {
+            rounds,
+            moves,
+            total,
+        }
+    }
+    // The summary of the moves the player made
+    pub fn move_summary(&self, hand: HandOption) -> &MoveSummary {
+        &self.moves[hand.move_index()]
+    }
+    pub fn total(&self) -> &MoveSummary {
+        &self.total
+    }
+    pub fn write<W: Write>(&self, format: ReportFormat, out: &mut W) -> io::Result<()> {
+        match format {
+            ReportFormat::Text => self.write_text(out),
+            ReportFormat::Csv => self.write_csv(out),
+            ReportFormat::Json => self.write_json(out),
+        }
+    }
+    // Round number (starting at 1), the round and the score after it
+    fn rounds_with_totals(&self) -> impl Iterator<Item = (usize, &Round, u64)> {
+        self.rounds
+            .iter()
+            .enumerate()
+            .scan(0, |total, (index, round)| {
+                *total += round.round_points();
+                Some((index + 1, round, *total))
+            })
+    }
+    fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>6} {:<9} {:<9} {:<7} {:>6} {:>8}",
+            "Round", "Opponent", "Player", "Outcome", "Points", "Total"
+        )?;
+        for (number, round, total) in self.rounds_with_totals() {
+            writeln!(
+                out,
+                "{:>6} {:<9} {:<9} {:<7} {:>6} {:>8}",
+                number,
+                round.opponent().name(),
+                round.player().hand().name(),
+                round.outcome().name(),
+                round.round_points(),
+                total
+            )?;
+        }
+        writeln!(
+            out,
+            "Total: {} points in {} rounds ({} wins, {} draws, {} losses)",
+            self.total.points,
+            self.total.played,
+            self.total.wins,
+            self.total.draws,
+            self.total.losses
+        )?;
+        for hand in HandOption::all() {
+            let summary = self.move_summary(hand);
+            writeln!(
+                out,
+                "{}: played {} ({} wins, {} draws, {} losses), {} points",
+                hand.name(),
+                summary.played,
+                summary.wins,
+                summary.draws,
+                summary.losses,
+                summary.points
+            )?;
+        }
+        Ok(())
+    }
+    // Three tables separated by blank lines: rounds, moves and outcomes
+    fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "round,opponent,player,outcome,points,total")?;
+        for (number, round, total) in self.rounds_with_totals() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                number,
+                round.opponent().name(),
+                round.player().hand().name(),
+                round.outcome().name(),
+                round.round_points(),
+                total
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out, "move,played,wins,draws,losses,points")?;
+        for hand in HandOption::all() {
+            let summary = self.move_summary(hand);
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                hand.name(),
+                summary.played,
+                summary.wins,
+                summary.draws,
+                summary.losses,
+                summary.points
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out, "outcome,count")?;
+        for outcome in Outcome::all() {
+            writeln!(out, "{},{}", outcome.name(), self.total.count(outcome))?;
+        }
+        Ok(())
+    }
+    fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "{{\"rounds\":[")?;
+        for (number, round, total) in self.rounds_with_totals() {
+            if number > 1 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                "{{\"round\":{},\"opponent\":\"{}\",\"player\":\"{}\",\"outcome\":\"{}\",\"points\":{},\"total\":{}}}",
+                number,
+                round.opponent().name(),
+                round.player().hand().name(),
+                round.outcome().name(),
+                round.round_points(),
+                total
+            )?;
+        }
+        write!(out, "],\"moves\":{{")?;
+        for (index, hand) in HandOption::all().iter().enumerate() {
+            if index > 0 {
+                write!(out, ",")?;
+            }
+            let summary = self.move_summary(*hand);
+            write!(
+                out,
+                "\"{}\":{{\"played\":{},\"wins\":{},\"draws\":{},\"losses\":{},\"points\":{}}}",
+                hand.name(),
+                summary.played,
+                summary.wins,
+                summary.draws,
+                summary.losses,
+                summary.points
+            )?;
+        }
+        write!(out, "}},\"outcomes\":{{")?;
+        for (index, outcome) in Outcome::all().iter().enumerate() {
+            if index > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, "\"{}\":{}", outcome.name(), self.total.count(*outcome))?;
+        }
+        writeln!(
+            out,
+            "}},\"rounds_played\":{},\"points\":{}}}",
+            self.total.played, self.total.points
+        )
+    }
+}
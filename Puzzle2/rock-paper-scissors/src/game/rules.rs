@@ -0,0 +1,293 @@
+use super::error::RockPaperScissorsError;
+use super::player::StrategyInterpretation;
+use super::scoring::ScoringProfile;
+use std::fs;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub fn points(&self) -> u32 {
+        match self {
+            Self::Loss => 0,
+            Self::Draw => 3,
+            Self::Win => 6,
+        }
+    }
+    pub fn all() -> [Outcome; 3] {
+        [Self::Win, Self::Draw, Self::Loss]
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Loss => "loss",
+            Self::Draw => "draw",
+            Self::Win => "win",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveRule {
+    name: String,
+    opponent_symbol: String,
+    player_symbol: String,
+    points: u32,
+}
+
+impl MoveRule {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn opponent_symbol(&self) -> &str {
+        &self.opponent_symbol
+    }
+    pub fn player_symbol(&self) -> &str {
+        &self.player_symbol
+    }
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+}
+
+// Moves are referred to by their position in the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    moves: Vec<MoveRule>,
+    // beats[a][b] is true when move a beats move b
+    beats: Vec<Vec<bool>>,
+    outcome_symbols: Vec<(String, Outcome)>,
+}
+
+static CLASSIC_RULES: OnceLock<RuleTable> = OnceLock::new();
+
+const CLASSIC_CONFIG: &str = "\
+move Rock A X 1
+move Paper B Y 2
+move Scissors C Z 3
+cyclic
+outcome lose X
+outcome draw Y
+outcome win Z
+";
+
+impl RuleTable {
+    pub fn classic() -> &'static RuleTable {
+        CLASSIC_RULES.get_or_init(|| Self::parse(CLASSIC_CONFIG).expect("Invalid classic rules"))
+    }
+
+    pub fn load(filename: &str) -> Result<Self, RockPaperScissorsError> {
+        let config = fs::read_to_string(filename)?;
+        Self::parse(&config)
+    }
+
+    // One rule per line, # starts a comment:
+    //   move <name> <opponent symbol> <player symbol> [points]
+    //   beats <name> <name>...   the first move beats every other one in the line
+    //   cyclic                   every move beats the (n - 1) / 2 moves declared before it, wrapping around
+    //   outcome <lose|draw|win> <player symbol>
+    // Shape points default to the position of the move, starting at 1
+    pub fn parse(config: &str) -> Result<Self, RockPaperScissorsError> {
+        let mut moves: Vec<MoveRule> = vec![];
+        let mut beats_lines = vec![];
+        let mut cyclic = false;
+        let mut outcome_symbols = vec![];
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let invalid_line = |reason: &str| {
+                RockPaperScissorsError::InvalidRules(format!("line {}: {}", line_index + 1, reason))
+            };
+            match words.as_slice() {
+                [] => {}
+                ["move", name, opponent_symbol, player_symbol, points @ ..] => {
+                    let points = match points {
+                        [] => moves.len() as u32 + 1,
+                        [points] => points
+                            .parse()
+                            .map_err(|_| invalid_line("invalid shape points"))?,
+                        _ => return Err(invalid_line("too many values for a move")),
+                    };
+                    moves.push(MoveRule {
+                        name: name.to_string(),
+                        opponent_symbol: opponent_symbol.to_string(),
+                        player_symbol: player_symbol.to_string(),
+                        points,
+                    });
+                }
+                ["beats", winner, losers @ ..] if !losers.is_empty() => {
+                    beats_lines.push((line_index + 1, winner.to_string(), losers.join(" ")));
+                }
+                ["cyclic"] => cyclic = true,
+                ["outcome", outcome, symbol] => {
+                    let outcome = match *outcome {
+                        "lose" | "loss" => Outcome::Loss,
+                        "draw" => Outcome::Draw,
+                        "win" => Outcome::Win,
+                        _ => return Err(invalid_line("outcome has to be lose, draw or win")),
+                    };
+                    outcome_symbols.push((symbol.to_string(), outcome));
+                }
+                _ => return Err(invalid_line(&format!("unknown rule: {}", line.trim()))),
+            }
+        }
+        let mut table = Self {
+            beats: vec![vec![false; moves.len()]; moves.len()],
+            moves,
+            outcome_symbols,
+        };
+        if cyclic {
+            table.add_cyclic_rules()?;
+        }
+        for (line_number, winner, losers) in beats_lines {
+            let winner = table.move_named(&winner, line_number)?;
+            for loser in losers.split(' ') {
+                let loser = table.move_named(loser, line_number)?;
+                table.beats[winner][loser] = true;
+            }
+        }
+        table.validate()?;
+        Ok(table)
+    }
+
+    fn move_named(&self, name: &str, line_number: usize) -> Result<usize, RockPaperScissorsError> {
+        self.moves
+            .iter()
+            .position(|rule| rule.name == name)
+            .ok_or_else(|| {
+                RockPaperScissorsError::InvalidRules(format!(
+                    "line {}: no such move: {}",
+                    line_number, name
+                ))
+            })
+    }
+
+    fn add_cyclic_rules(&mut self) -> Result<(), RockPaperScissorsError> {
+        let move_count = self.moves.len();
+        if move_count.is_multiple_of(2) {
+            return Err(RockPaperScissorsError::InvalidRules(format!(
+                "cyclic games need an odd number of moves, found {}",
+                move_count
+            )));
+        }
+        for winner in 0..move_count {
+            for distance in 1..=(move_count - 1) / 2 {
+                let loser = (winner + move_count - distance) % move_count;
+                self.beats[winner][loser] = true;
+            }
+        }
+        Ok(())
+    }
+
+    // Every pair of different moves needs exactly one winner
+    fn validate(&self) -> Result<(), RockPaperScissorsError> {
+        if self.moves.is_empty() {
+            return Err(RockPaperScissorsError::InvalidRules(String::from(
+                "there are no moves",
+            )));
+        }
+        for (index, rule) in self.moves.iter().enumerate() {
+            let repeated = self.moves[..index].iter().find(|other| {
+                other.name == rule.name
+                    || other.opponent_symbol == rule.opponent_symbol
+                    || other.player_symbol == rule.player_symbol
+            });
+            if let Some(other) = repeated {
+                return Err(RockPaperScissorsError::InvalidRules(format!(
+                    "{} and {} share a name or a symbol",
+                    other.name, rule.name
+                )));
+            }
+        }
+        for first in 0..self.moves.len() {
+            if self.beats[first][first] {
+                return Err(RockPaperScissorsError::InvalidRules(format!(
+                    "{} can't beat itself",
+                    self.moves[first].name
+                )));
+            }
+            for second in first + 1..self.moves.len() {
+                if self.beats[first][second] == self.beats[second][first] {
+                    return Err(RockPaperScissorsError::InvalidRules(format!(
+                        "{} and {} need exactly one winner",
+                        self.moves[first].name, self.moves[second].name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn moves(&self) -> &[MoveRule] {
+        &self.moves
+    }
+
+    pub fn beats(&self, winner: usize, loser: usize) -> bool {
+        self.beats[winner][loser]
+    }
+
+    pub fn outcome(&self, player: usize, opponent: usize) -> Outcome {
+        if self.beats[player][opponent] {
+            Outcome::Win
+        } else if self.beats[opponent][player] {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    // Shape points come from the profile when it knows the move or has a default, otherwise from the table.
+    // Both parts can be as big as a u32, so their sum is a u64
+    pub fn round_points(&self, player: usize, opponent: usize, profile: &ScoringProfile) -> u64 {
+        let shape_points = profile
+            .shape_points(&self.moves[player].name)
+            .unwrap_or(self.moves[player].points);
+        u64::from(shape_points) + u64::from(profile.outcome_points(self.outcome(player, opponent)))
+    }
+
+    pub fn parse_opponent(&self, symbol: &str) -> Result<usize, RockPaperScissorsError> {
+        self.moves
+            .iter()
+            .position(|rule| rule.opponent_symbol == symbol)
+            .ok_or_else(|| RockPaperScissorsError::from(format!("No such move: {}", symbol)))
+    }
+
+    // With more than three moves several of them can give the same outcome, the first one declared is used
+    pub fn parse_player_move(
+        &self,
+        opponent: usize,
+        symbol: &str,
+        interpretation: StrategyInterpretation,
+    ) -> Result<usize, RockPaperScissorsError> {
+        let no_such_move = || RockPaperScissorsError::from(format!("No such move: {}", symbol));
+        match interpretation {
+            StrategyInterpretation::Move => self
+                .moves
+                .iter()
+                .position(|rule| rule.player_symbol == symbol)
+                .ok_or_else(no_such_move),
+            StrategyInterpretation::Outcome => {
+                let outcome = self
+                    .outcome_symbols
+                    .iter()
+                    .find(|(outcome_symbol, _)| outcome_symbol == symbol)
+                    .map(|(_, outcome)| *outcome)
+                    .ok_or_else(no_such_move)?;
+                self.move_for_outcome(opponent, outcome).ok_or_else(|| {
+                    RockPaperScissorsError::InvalidGame(format!(
+                        "No move gives a {:?} against {}",
+                        outcome, self.moves[opponent].name
+                    ))
+                })
+            }
+        }
+    }
+
+    pub fn move_for_outcome(&self, opponent: usize, outcome: Outcome) -> Option<usize> {
+        (0..self.moves.len()).find(|player| self.outcome(*player, opponent) == outcome)
+    }
+}
@@ -0,0 +1,153 @@
+use super::error::RockPaperScissorsError;
+use super::hand_option::HandOption;
+use super::rules::Outcome;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoringProfile {
+    // Points for playing a move, by move name. Moves missing here fall back to the rule table
+    shape_points: Vec<(String, u32)>,
+    // Points for every move missing from shape_points, when set the rule table's points aren't used
+    default_shape_points: Option<u32>,
+    loss: u32,
+    draw: u32,
+    win: u32,
+}
+
+pub const PRESETS: [&str; 3] = ["standard", "wins-only", "league"];
+
+impl ScoringProfile {
+    // Shape points come from the rule table (1/2/3 for the classic one) and 0/3/6 for a loss/draw/win
+    pub fn standard() -> Self {
+        Self {
+            shape_points: vec![],
+            default_shape_points: None,
+            loss: Outcome::Loss.points(),
+            draw: Outcome::Draw.points(),
+            win: Outcome::Win.points(),
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::standard()),
+            // Only winning counts
+            "wins-only" => Some(Self::without_shapes(0, 0, 1)),
+            // Football league table: 3 for a win, 1 for a draw
+            "league" => Some(Self::without_shapes(0, 1, 3)),
+            _ => None,
+        }
+    }
+
+    // No move scores anything, whatever the rule table says
+    fn without_shapes(loss: u32, draw: u32, win: u32) -> Self {
+        Self {
+            shape_points: vec![],
+            default_shape_points: Some(0),
+            loss,
+            draw,
+            win,
+        }
+    }
+
+    // A preset name, or else the file to load the profile from
+    pub fn from_preset_or_file(name: &str) -> Result<Self, RockPaperScissorsError> {
+        match Self::preset(name) {
+            Some(profile) => Ok(profile),
+            None => Self::load(name),
+        }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, RockPaperScissorsError> {
+        let config = fs::read_to_string(filename)?;
+        Self::parse(&config)
+    }
+
+    // Starts from the standard profile, then one override per line, # starts a comment:
+    //   preset <name>              replaces everything with the preset
+    //   shape <move name> <points>  (* as the name sets the points of every move not named)
+    //   outcome <lose|draw|win> <points>
+    pub fn parse(config: &str) -> Result<Self, RockPaperScissorsError> {
+        let mut profile = Self::standard();
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let invalid_line = |reason: String| {
+                RockPaperScissorsError::InvalidScoring(format!(
+                    "line {}: {}",
+                    line_index + 1,
+                    reason
+                ))
+            };
+            let parse_points = |points: &str| {
+                points
+                    .parse::<u32>()
+                    .map_err(|_| invalid_line(format!("invalid points: {}", points)))
+            };
+            match words.as_slice() {
+                [] => {}
+                ["preset", name] => {
+                    profile = Self::preset(name)
+                        .ok_or_else(|| invalid_line(format!("no such preset: {}", name)))?;
+                }
+                ["shape", "*", points] => {
+                    profile.default_shape_points = Some(parse_points(points)?);
+                }
+                ["shape", name, points] => {
+                    let points = parse_points(points)?;
+                    match profile
+                        .shape_points
+                        .iter_mut()
+                        .find(|(shape, _)| shape == name)
+                    {
+                        Some((_, shape_points)) => *shape_points = points,
+                        None => profile.shape_points.push((name.to_string(), points)),
+                    }
+                }
+                ["outcome", outcome, points] => {
+                    let points = parse_points(points)?;
+                    match *outcome {
+                        "lose" | "loss" => profile.loss = points,
+                        "draw" => profile.draw = points,
+                        "win" => profile.win = points,
+                        _ => {
+                            return Err(invalid_line(format!(
+                                "outcome has to be lose, draw or win, found {}",
+                                outcome
+                            )))
+                        }
+                    }
+                }
+                _ => return Err(invalid_line(format!("unknown rule: {}", line.trim()))),
+            }
+        }
+        Ok(profile)
+    }
+
+    pub fn shape_points(&self, move_name: &str) -> Option<u32> {
+        self.shape_points
+            .iter()
+            .find(|(shape, _)| shape == move_name)
+            .map(|(_, points)| *points)
+            .or(self.default_shape_points)
+    }
+
+    pub fn hand_points(&self, hand: HandOption) -> u32 {
+        self.shape_points(hand.name())
+            .unwrap_or(hand.move_index() as u32 + 1)
+    }
+
+    pub fn outcome_points(&self, outcome: Outcome) -> u32 {
+        match outcome {
+            Outcome::Loss => self.loss,
+            Outcome::Draw => self.draw,
+            Outcome::Win => self.win,
+        }
+    }
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
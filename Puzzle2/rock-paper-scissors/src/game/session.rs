@@ -0,0 +1,137 @@
+use super::error::RockPaperScissorsError;
+use super::game::{resolve_round_points, Round};
+use super::hand_option::HandOption;
+use super::opponent::parse_opponent;
+use super::player::{parse_player_move, StrategyInterpretation};
+use super::scoring::ScoringProfile;
+use super::tournament::{Strategy, StrategySpec};
+
+pub enum OpponentSource {
+    // The opponent's move is typed along with ours, like a line of a guide ("A Y")
+    Manual,
+    // A bot picks its move before seeing ours
+    Bot(StrategySpec),
+    // The opponent plays the first column of a guide, in order
+    Guide(Vec<HandOption>),
+}
+
+pub struct PlaySession {
+    source: OpponentSource,
+    interpretation: StrategyInterpretation,
+    profile: ScoringProfile,
+    rounds: Vec<Round>,
+    bot: Option<Box<dyn Strategy>>,
+    // The bot commits to its move before we type ours
+    bot_move: Option<HandOption>,
+}
+
+impl PlaySession {
+    pub fn new(
+        source: OpponentSource,
+        interpretation: StrategyInterpretation,
+        profile: ScoringProfile,
+    ) -> Self {
+        let bot = match &source {
+            OpponentSource::Bot(spec) => Some(spec.build()),
+            _ => None,
+        };
+        Self {
+            source,
+            interpretation,
+            profile,
+            rounds: vec![],
+            bot,
+            bot_move: None,
+        }
+    }
+
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    pub fn score(&self) -> u64 {
+        self.rounds.iter().map(|round| round.round_points()).sum()
+    }
+
+    pub fn profile(&self) -> &ScoringProfile {
+        &self.profile
+    }
+
+    // Only a guide can run out of rounds
+    pub fn is_over(&self) -> bool {
+        match &self.source {
+            OpponentSource::Guide(opponents) => self.rounds.len() >= opponents.len(),
+            _ => false,
+        }
+    }
+
+    // What the guide says the opponent plays next, bots keep their move secret
+    pub fn announced_opponent(&self) -> Option<HandOption> {
+        match &self.source {
+            OpponentSource::Guide(opponents) => opponents.get(self.rounds.len()).copied(),
+            _ => None,
+        }
+    }
+
+    fn next_opponent(
+        &mut self,
+        opponent_str: Option<&str>,
+    ) -> Result<HandOption, RockPaperScissorsError> {
+        match (&self.source, opponent_str) {
+            (OpponentSource::Manual, Some(opponent_str)) => parse_opponent(opponent_str),
+            (OpponentSource::Manual, None) => Err(RockPaperScissorsError::InvalidGame(
+                String::from("Type the opponent's move too, like A Y"),
+            )),
+            (_, Some(_)) => Err(RockPaperScissorsError::InvalidGame(String::from(
+                "The opponent chooses its own move, type only yours",
+            ))),
+            (OpponentSource::Guide(_), None) => self.announced_opponent().ok_or_else(|| {
+                RockPaperScissorsError::InvalidGame(String::from("The guide has no rounds left"))
+            }),
+            (OpponentSource::Bot(_), None) => {
+                let bot = self.bot.as_mut().expect("Bot sessions have a bot");
+                Ok(*self.bot_move.get_or_insert_with(|| bot.next_move()))
+            }
+        }
+    }
+
+    // Plays "Y" against a bot or a guide, or "A Y" when playing both sides, and returns the round's points
+    pub fn play(&mut self, input: &str) -> Result<u64, RockPaperScissorsError> {
+        let symbols = input.split_whitespace().collect::<Vec<_>>();
+        let (opponent_str, player_str) = match symbols.as_slice() {
+            [player_str] => (None, *player_str),
+            [opponent_str, player_str] => (Some(*opponent_str), *player_str),
+            _ => {
+                return Err(RockPaperScissorsError::InvalidGame(format!(
+                    "Expected a move, found: {}",
+                    input
+                )))
+            }
+        };
+        let opponent = self.next_opponent(opponent_str)?;
+        let player = parse_player_move(opponent, player_str, self.interpretation)?;
+        let round_points = resolve_round_points(opponent, player, &self.profile);
+        let round = Round::from_moves(opponent, player.hand(), &self.profile);
+        if let Some(bot) = self.bot.as_mut() {
+            bot.observe(opponent, player.hand());
+            self.bot_move = None;
+        }
+        self.rounds.push(round);
+        Ok(round_points)
+    }
+
+    // Bots can't forget what they saw, so they are rebuilt and shown the remaining rounds again
+    pub fn undo(&mut self) -> Option<Round> {
+        let round = self.rounds.pop()?;
+        if let OpponentSource::Bot(spec) = &self.source {
+            let mut bot = spec.build();
+            for played in self.rounds.iter() {
+                bot.next_move();
+                bot.observe(played.opponent(), played.player().hand());
+            }
+            self.bot = Some(bot);
+            self.bot_move = None;
+        }
+        Some(round)
+    }
+}
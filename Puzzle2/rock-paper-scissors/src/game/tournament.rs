@@ -0,0 +1,336 @@
+use super::game::Round;
+use super::hand_option::HandOption;
+use super::scoring::ScoringProfile;
+use std::str::FromStr;
+
+pub trait Strategy {
+    fn next_move(&mut self) -> HandOption;
+    // Called after every round with both moves
+    fn observe(&mut self, own: HandOption, opponent: HandOption);
+}
+
+// Plays the same sequence over and over
+pub struct FixedSequence {
+    moves: Vec<HandOption>,
+    position: usize,
+}
+
+impl Strategy for FixedSequence {
+    fn next_move(&mut self) -> HandOption {
+        let next_move = self.moves[self.position % self.moves.len()];
+        self.position += 1;
+        next_move
+    }
+    fn observe(&mut self, _own: HandOption, _opponent: HandOption) {}
+}
+
+fn most_frequent(counts: &[u32; 3]) -> Option<HandOption> {
+    let (move_index, count) =
+        counts
+            .iter()
+            .enumerate()
+            .reduce(|best, count| if count.1 > best.1 { count } else { best })?;
+    if *count == 0 {
+        return None;
+    }
+    HandOption::from_move_index(move_index)
+}
+
+// Beats whatever the opponent played the most so far
+#[derive(Default)]
+pub struct FrequencyCounter {
+    counts: [u32; 3],
+}
+
+impl Strategy for FrequencyCounter {
+    fn next_move(&mut self) -> HandOption {
+        most_frequent(&self.counts)
+            .map(|hand| hand.counter())
+            .unwrap_or(HandOption::Rock)
+    }
+    fn observe(&mut self, _own: HandOption, opponent: HandOption) {
+        self.counts[opponent.move_index()] += 1;
+    }
+}
+
+// Guesses the opponent's next move from what usually followed its last one, and beats it
+#[derive(Default)]
+pub struct MarkovPredictor {
+    transitions: [[u32; 3]; 3],
+    counts: [u32; 3],
+    last_opponent_move: Option<HandOption>,
+}
+
+impl Strategy for MarkovPredictor {
+    fn next_move(&mut self) -> HandOption {
+        let predicted = self
+            .last_opponent_move
+            .and_then(|last_move| most_frequent(&self.transitions[last_move.move_index()]))
+            .or_else(|| most_frequent(&self.counts));
+        predicted
+            .map(|hand| hand.counter())
+            .unwrap_or(HandOption::Rock)
+    }
+    fn observe(&mut self, _own: HandOption, opponent: HandOption) {
+        if let Some(last_move) = self.last_opponent_move {
+            self.transitions[last_move.move_index()][opponent.move_index()] += 1;
+        }
+        self.counts[opponent.move_index()] += 1;
+        self.last_opponent_move = Some(opponent);
+    }
+}
+
+// splitmix64, so the same seed always plays the same moves
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Strategy for SeededRandom {
+    fn next_move(&mut self) -> HandOption {
+        let move_index = (self.next_u64() % 3) as usize;
+        HandOption::from_move_index(move_index).expect("There are three hand options")
+    }
+    fn observe(&mut self, _own: HandOption, _opponent: HandOption) {}
+}
+
+// Describes a strategy, every match builds a fresh one so nothing is remembered between matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrategySpec {
+    Fixed(Vec<HandOption>),
+    Frequency,
+    Markov,
+    Random(u64),
+}
+
+impl StrategySpec {
+    pub fn build(&self) -> Box<dyn Strategy> {
+        match self {
+            Self::Fixed(moves) => Box::new(FixedSequence {
+                moves: moves.clone(),
+                position: 0,
+            }),
+            Self::Frequency => Box::<FrequencyCounter>::default(),
+            Self::Markov => Box::<MarkovPredictor>::default(),
+            Self::Random(seed) => Box::new(SeededRandom::new(*seed)),
+        }
+    }
+    pub fn name(&self) -> String {
+        match self {
+            Self::Fixed(moves) => {
+                let sequence = moves
+                    .iter()
+                    .map(|hand| &hand.name()[..1])
+                    .collect::<String>();
+                format!("fixed:{}", sequence)
+            }
+            Self::Frequency => String::from("frequency"),
+            Self::Markov => String::from("markov"),
+            Self::Random(seed) => format!("random:{}", seed),
+        }
+    }
+}
+
+// fixed:<moves> (like fixed:RRPS), frequency, markov or random:<seed>
+impl FromStr for StrategySpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (kind, argument) = match spec.split_once(':') {
+            Some((kind, argument)) => (kind, Some(argument)),
+            None => (spec, None),
+        };
+        match (kind, argument) {
+            ("fixed", Some(sequence)) if !sequence.is_empty() => sequence
+                .chars()
+                .map(|hand| hand.to_string().parse::<HandOption>())
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self::Fixed),
+            ("frequency", None) => Ok(Self::Frequency),
+            ("markov", None) => Ok(Self::Markov),
+            ("random", Some(seed)) => seed
+                .parse()
+                .map(Self::Random)
+                .map_err(|_| format!("Invalid seed: {}", seed)),
+            ("random", None) => Ok(Self::Random(0)),
+            _ => Err(format!(
+                "Unknown strategy {}, expected fixed:<moves>, frequency, markov or random:<seed>",
+                spec
+            )),
+        }
+    }
+}
+
+pub struct MatchResult {
+    // Indices of the strategies in the tournament
+    first: usize,
+    second: usize,
+    first_timeline: Vec<u64>,
+    second_timeline: Vec<u64>,
+}
+
+impl MatchResult {
+    pub fn first(&self) -> usize {
+        self.first
+    }
+    pub fn second(&self) -> usize {
+        self.second
+    }
+    // Cumulative score after every round
+    pub fn first_timeline(&self) -> &[u64] {
+        &self.first_timeline
+    }
+    pub fn second_timeline(&self) -> &[u64] {
+        &self.second_timeline
+    }
+    pub fn first_score(&self) -> u64 {
+        self.first_timeline.last().copied().unwrap_or(0)
+    }
+    pub fn second_score(&self) -> u64 {
+        self.second_timeline.last().copied().unwrap_or(0)
+    }
+}
+
+pub fn play_match(
+    first: &StrategySpec,
+    second: &StrategySpec,
+    rounds: usize,
+    profile: &ScoringProfile,
+) -> (Vec<u64>, Vec<u64>) {
+    let mut first_strategy = first.build();
+    let mut second_strategy = second.build();
+    let mut first_timeline = Vec::with_capacity(rounds);
+    let mut second_timeline = Vec::with_capacity(rounds);
+    let (mut first_score, mut second_score) = (0, 0);
+    for _ in 0..rounds {
+        let first_move = first_strategy.next_move();
+        let second_move = second_strategy.next_move();
+        first_score += Round::from_moves(second_move, first_move, profile).round_points();
+        second_score += Round::from_moves(first_move, second_move, profile).round_points();
+        first_strategy.observe(first_move, second_move);
+        second_strategy.observe(second_move, first_move);
+        first_timeline.push(first_score);
+        second_timeline.push(second_score);
+    }
+    (first_timeline, second_timeline)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Standing {
+    points: u64,
+    match_wins: u32,
+    match_draws: u32,
+    match_losses: u32,
+}
+
+impl Standing {
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+    pub fn match_wins(&self) -> u32 {
+        self.match_wins
+    }
+    pub fn match_draws(&self) -> u32 {
+        self.match_draws
+    }
+    pub fn match_losses(&self) -> u32 {
+        self.match_losses
+    }
+}
+
+pub struct Tournament {
+    strategies: Vec<StrategySpec>,
+    matches: Vec<MatchResult>,
+}
+
+impl Tournament {
+    // Round robin: every strategy plays every other one once
+    pub fn round_robin(
+        strategies: Vec<StrategySpec>,
+        rounds: usize,
+        profile: &ScoringProfile,
+    ) -> Self {
+        let mut matches = vec![];
+        for first in 0..strategies.len() {
+            for second in first + 1..strategies.len() {
+                let (first_timeline, second_timeline) =
+                    play_match(&strategies[first], &strategies[second], rounds, profile);
+                matches.push(MatchResult {
+                    first,
+                    second,
+                    first_timeline,
+                    second_timeline,
+                });
+            }
+        }
+        Self {
+            strategies,
+            matches,
+        }
+    }
+    pub fn strategies(&self) -> &[StrategySpec] {
+        &self.strategies
+    }
+    pub fn matches(&self) -> &[MatchResult] {
+        &self.matches
+    }
+    // Indices into strategies(), best first (most points, then most match wins)
+    pub fn standings(&self) -> Vec<(usize, Standing)> {
+        let mut standings = vec![Standing::default(); self.strategies.len()];
+        for result in self.matches.iter() {
+            let (first_score, second_score) = (result.first_score(), result.second_score());
+            standings[result.first].points += first_score;
+            standings[result.second].points += second_score;
+            match first_score.cmp(&second_score) {
+                std::cmp::Ordering::Greater => {
+                    standings[result.first].match_wins += 1;
+                    standings[result.second].match_losses += 1;
+                }
+                std::cmp::Ordering::Less => {
+                    standings[result.first].match_losses += 1;
+                    standings[result.second].match_wins += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    standings[result.first].match_draws += 1;
+                    standings[result.second].match_draws += 1;
+                }
+            }
+        }
+        let mut standings = standings.into_iter().enumerate().collect::<Vec<_>>();
+        standings.sort_by(|(_, x), (_, y)| {
+            y.points
+                .cmp(&x.points)
+                .then_with(|| y.match_wins.cmp(&x.match_wins))
+        });
+        standings
+    }
+    // Cumulative score of a strategy over all its matches, in the order they were played
+    pub fn timeline(&self, strategy: usize) -> Vec<u64> {
+        let mut timeline = vec![];
+        let mut offset = 0;
+        for result in self.matches.iter() {
+            let match_timeline = if result.first == strategy {
+                result.first_timeline()
+            } else if result.second == strategy {
+                result.second_timeline()
+            } else {
+                continue;
+            };
+            timeline.extend(match_timeline.iter().map(|score| offset + *score));
+            offset = timeline.last().copied().unwrap_or(offset);
+        }
+        timeline
+    }
+}
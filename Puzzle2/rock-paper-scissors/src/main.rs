@@ -1,18 +1,172 @@
 use clap::Parser;
-use rock_paper_scissors::game::game;
+use rock_paper_scissors::game::error::RockPaperScissorsError;
+use rock_paper_scissors::game::game::{self, LoadMode};
+use rock_paper_scissors::game::optimizer::{self, Mapping, MappingAnalysis};
+use rock_paper_scissors::game::player::StrategyInterpretation;
+use rock_paper_scissors::game::report::{GameReport, ReportFormat};
+use rock_paper_scissors::game::rules::RuleTable;
+use rock_paper_scissors::game::scoring::ScoringProfile;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    // Reads the guide from stdin when missing or set to "-"
     #[arg(short, long)]
-    filename: String,
+    filename: Option<String>,
+    // Skips invalid rounds and lists them instead of stopping at the first one
+    #[arg(action, short, long)]
+    lenient: bool,
+    // move (X/Y/Z are Rock/Paper/Scissors) or outcome (X/Y/Z are lose/draw/win)
+    #[arg(short, long, default_value = "outcome")]
+    interpretation: StrategyInterpretation,
+    // Scores the guide under both interpretations
+    #[arg(action, short, long, conflicts_with = "interpretation")]
+    compare: bool,
+    // File with the moves, symbols and who beats whom, defaults to Rock/Paper/Scissors
+    #[arg(short, long)]
+    rules: Option<String>,
+    // A preset (standard, wins-only, league) or a scoring profile file
+    #[arg(short, long, default_value = "standard")]
+    scoring: String,
+    // Tries every meaning of X/Y/Z and compares the best one with the chosen interpretation
+    #[arg(action, short, long, conflicts_with_all = ["compare", "rules"])]
+    optimize: bool,
+    // Prints every round and how each move did, as text, csv or json
+    #[arg(long, conflicts_with_all = ["compare", "rules", "optimize"])]
+    report: Option<ReportFormat>,
+}
+
+fn exit_with<E: Display>(error: E) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
+}
+
+fn open_guide(args: &Args) -> Box<dyn BufRead> {
+    match args.filename.as_deref() {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(filename) => {
+            Box::new(BufReader::new(File::open(filename).unwrap_or_else(|e| {
+                exit_with(format!("Couldn't open {}: {}", filename, e))
+            })))
+        }
+    }
+}
+
+fn load_mode(args: &Args) -> LoadMode {
+    if args.lenient {
+        LoadMode::Lenient
+    } else {
+        LoadMode::Strict
+    }
+}
+
+fn report_skipped(skipped: &[RockPaperScissorsError]) {
+    if skipped.is_empty() {
+        return;
+    }
+    eprintln!("Skipped {} invalid rounds:", skipped.len());
+    for error in skipped {
+        eprintln!("  {}", error);
+    }
+}
+
+fn optimize(args: &Args, profile: &ScoringProfile) {
+    let (guide, skipped) =
+        optimizer::load_guide(open_guide(args), load_mode(args)).unwrap_or_else(|e| exit_with(e));
+    report_skipped(&skipped);
+    let analysis = MappingAnalysis::new(guide, profile);
+    let mut scores = analysis.scores().iter().collect::<Vec<_>>();
+    scores.sort_by_key(|score| std::cmp::Reverse(score.total()));
+    for score in scores {
+        println!("{}: {}", score.mapping(), score.total());
+    }
+    let best = match analysis.best() {
+        Some(best) => best,
+        None => return,
+    };
+    println!("Best mapping: {} ({} points)", best.mapping(), best.total());
+    let chosen = analysis
+        .score(Mapping::from_interpretation(args.interpretation))
+        .expect("Every interpretation is a mapping");
+    let changed_rounds = analysis.changed_rounds(chosen, best);
+    println!(
+        "Rounds with a different result than {}: {}",
+        args.interpretation.description(),
+        changed_rounds.len()
+    );
+    for round in changed_rounds {
+        println!(
+            "  line {}: {} {}",
+            round.line(),
+            round.opponent().name(),
+            round.symbol()
+        );
+    }
+    println!(
+        "Rounds whose result depends on the mapping: {} of {}",
+        analysis.contested_rounds().len(),
+        analysis.guide().len()
+    );
 }
 
 fn main() {
     let args = Args::parse();
-    let rounds = game::load_game(&args.filename).unwrap();
-    let sum_points = rounds
-        .iter()
-        .fold(0, |accum, iter| accum + iter.round_points());
-    println!("Sum points: {}", sum_points);
+    let loaded_rules;
+    let rules = match &args.rules {
+        Some(rules_filename) => {
+            loaded_rules = RuleTable::load(rules_filename).unwrap_or_else(|e| exit_with(e));
+            &loaded_rules
+        }
+        None => RuleTable::classic(),
+    };
+    let profile =
+        ScoringProfile::from_preset_or_file(&args.scoring).unwrap_or_else(|e| exit_with(e));
+    if let Some(format) = args.report {
+        let (rounds, skipped) = game::load_game_from(
+            open_guide(&args),
+            args.interpretation,
+            &profile,
+            load_mode(&args),
+        )
+        .unwrap_or_else(|e| exit_with(e));
+        report_skipped(&skipped);
+        GameReport::new(&rounds)
+            .write(format, &mut io::stdout().lock())
+            .expect("Couldn't write report");
+        return;
+    }
+    if args.optimize {
+        optimize(&args, &profile);
+        return;
+    }
+    if args.compare {
+        let interpretations = StrategyInterpretation::all();
+        let (totals, skipped) = game::load_game_totals(
+            open_guide(&args),
+            rules,
+            &profile,
+            &interpretations,
+            load_mode(&args),
+        )
+        .unwrap_or_else(|e| exit_with(e));
+        report_skipped(&skipped);
+        for (interpretation, total) in interpretations.iter().zip(totals) {
+            println!("Sum points ({}): {}", interpretation.description(), total);
+        }
+        return;
+    }
+    let (totals, skipped) = game::load_game_totals(
+        open_guide(&args),
+        rules,
+        &profile,
+        &[args.interpretation],
+        load_mode(&args),
+    )
+    .unwrap_or_else(|e| exit_with(e));
+    report_skipped(&skipped);
+    println!("Sum points: {}", totals[0]);
 }
@@ -1,16 +1,173 @@
 use clap::Parser;
-use rucksack_items::rucksack::loader;
+use rucksack_items::rucksack::error::RucksackError;
+use rucksack_items::rucksack::grouping::GroupingSpec;
+use rucksack_items::rucksack::inference;
+use rucksack_items::rucksack::item::PriorityScheme;
+use rucksack_items::rucksack::loader::{self, LoadMode};
+use rucksack_items::rucksack::repack;
+use std::fmt::Display;
 
 #[derive(Debug, Parser)]
 #[command(author, version)]
 struct Args {
     #[arg(short, long)]
     filename: String,
+    // Analyze each rucksack's compartments instead of the badge groups
+    #[arg(short, long)]
+    compartments: bool,
+    // Plan the fewest swaps that leave no item type in both compartments
+    #[arg(short, long)]
+    repack: bool,
+    // consecutive:<size>, sliding:<size> or ids (lines start with a group id)
+    #[arg(short, long, default_value = "consecutive:3")]
+    grouping: GroupingSpec,
+    // Work out the groups of three when the rucksacks have been shuffled
+    #[arg(short, long, conflicts_with = "grouping")]
+    infer: bool,
+    // A preset (standard, latin1) or a priority scheme file
+    #[arg(short, long, default_value = "standard")]
+    priorities: String,
+    // Stop at the first malformed rucksack instead of skipping it
+    #[arg(short, long)]
+    strict: bool,
+    // Print every group's badge and common items
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+fn load_mode(args: &Args) -> LoadMode {
+    if args.strict {
+        LoadMode::Strict
+    } else {
+        LoadMode::Lenient
+    }
+}
+
+fn exit_with<E: Display>(error: E) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
+
+fn report_skipped(skipped: &[RucksackError]) {
+    if skipped.is_empty() {
+        return;
+    }
+    eprintln!("Skipped {} malformed rucksacks:", skipped.len());
+    for error in skipped {
+        eprintln!("  {}", error);
+    }
 }
 
 fn main() {
     let args = Args::parse();
-    let badgegroups = loader::load_badgegroup(&args.filename);
+    let mode = load_mode(&args);
+    let scheme =
+        PriorityScheme::from_preset_or_file(&args.priorities).unwrap_or_else(|e| exit_with(e));
+    if args.compartments || args.repack {
+        let (rucksacks, skipped) =
+            loader::load_rucksack(&args.filename, &scheme, mode).unwrap_or_else(|e| exit_with(e));
+        report_skipped(&skipped);
+        if args.repack {
+            for (index, rucksack) in rucksacks.iter().enumerate() {
+                match repack::plan_repack(rucksack.analysis(), &scheme) {
+                    Ok(plan) => {
+                        let swaps = plan
+                            .swaps()
+                            .iter()
+                            .map(|swap| {
+                                format!(
+                                    "{}@{} <-> {}@{}",
+                                    swap.first_item,
+                                    swap.first_position,
+                                    swap.second_item,
+                                    swap.second_position
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        println!(
+                            "Rucksack {}: {} swaps [{}] => {} | {}",
+                            index + 1,
+                            swaps.len(),
+                            swaps.join(", "),
+                            plan.first(),
+                            plan.second()
+                        );
+                    }
+                    Err(error) => println!("Rucksack {}: can't repack, {}", index + 1, error),
+                }
+            }
+            return;
+        }
+        for rucksack in rucksacks.iter() {
+            let analysis = rucksack.analysis();
+            let shared = analysis
+                .shared()
+                .iter()
+                .map(|shared| {
+                    format!(
+                        "{} ({}/{})",
+                        shared.item, shared.first_count, shared.second_count
+                    )
+                })
+                .collect::<Vec<_>>();
+            println!(
+                "{} | {}: shared [{}], only first [{}], only second [{}]",
+                analysis.first().items(),
+                analysis.second().items(),
+                shared.join(", "),
+                analysis.only_in_first().iter().collect::<String>(),
+                analysis.only_in_second().iter().collect::<String>()
+            );
+        }
+        let sum_priorities = rucksacks.iter().fold(0, |acc, item| acc + item.points());
+        println!("Sum priorities: {}", sum_priorities);
+        return;
+    }
+    if args.infer {
+        let (lines, skipped) =
+            loader::load_item_lines(&args.filename, &scheme, mode).unwrap_or_else(|e| exit_with(e));
+        report_skipped(&skipped);
+        let rucksacks = lines
+            .iter()
+            .map(|line| line.items.as_str())
+            .collect::<Vec<_>>();
+        let groups =
+            inference::infer_badge_groups(&rucksacks, &scheme).unwrap_or_else(|e| exit_with(e));
+        if args.verbose {
+            for group in groups.iter() {
+                let line_numbers = group.members().map(|member| lines[member].line.to_string());
+                println!(
+                    "Lines {}: badge {}",
+                    line_numbers.join(", "),
+                    group.badge_group().repeated_char()
+                );
+            }
+        }
+        let sum_priorities = groups
+            .iter()
+            .fold(0, |acc, group| acc + group.badge_group().points());
+        println!("Sum priorities: {}", sum_priorities);
+        return;
+    }
+    let (badgegroups, skipped) = loader::load_badgegroups(
+        &args.filename,
+        args.grouping.build().as_ref(),
+        &scheme,
+        mode,
+    )
+    .unwrap_or_else(|e| exit_with(e));
+    report_skipped(&skipped);
+    if args.verbose {
+        for (index, badgegroup) in badgegroups.iter().enumerate() {
+            println!(
+                "Group {} ({} elves): badge {}, common [{}]",
+                index + 1,
+                badgegroup.size(),
+                badgegroup.repeated_char(),
+                badgegroup.common_items().iter().collect::<String>()
+            );
+        }
+    }
     let sum_priorities = badgegroups.iter().fold(0, |acc, item| acc + item.points());
     println!("Sum priorities: {}", sum_priorities);
 }
@@ -0,0 +1,139 @@
+use super::error::CompartmentError;
+use super::item::{ItemSet, PriorityScheme};
+
+#[derive(Debug, Clone)]
+pub struct Compartment {
+    items: String,
+    item_types: ItemSet,
+    // By item type index
+    counts: Vec<u32>,
+}
+
+impl Compartment {
+    // offset is the position of the compartment's first item in the rucksack
+    fn new(items: &str, offset: usize, scheme: &PriorityScheme) -> Result<Self, CompartmentError> {
+        let mut item_types = ItemSet::new();
+        let mut counts = vec![0; scheme.len()];
+        for (position, current_item) in items.chars().enumerate() {
+            let index = scheme
+                .index(current_item)
+                .ok_or(CompartmentError::InvalidItem {
+                    item: current_item,
+                    position: offset + position + 1,
+                })?;
+            item_types.insert(index);
+            counts[index] += 1;
+        }
+        Ok(Self {
+            items: items.to_string(),
+            item_types,
+            counts,
+        })
+    }
+    pub fn items(&self) -> &str {
+        &self.items
+    }
+    pub fn item_types(&self) -> &ItemSet {
+        &self.item_types
+    }
+    pub fn count(&self, index: usize) -> u32 {
+        self.counts.get(index).copied().unwrap_or_default()
+    }
+    pub fn len(&self) -> usize {
+        self.items.chars().count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedItem {
+    pub item: char,
+    pub first_count: u32,
+    pub second_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompartmentAnalysis {
+    first: Compartment,
+    second: Compartment,
+    shared: Vec<SharedItem>,
+    only_in_first: Vec<char>,
+    only_in_second: Vec<char>,
+    first_shared: Option<char>,
+}
+
+impl CompartmentAnalysis {
+    pub fn new(rucksack_items: &str) -> Result<Self, CompartmentError> {
+        Self::with_scheme(rucksack_items, PriorityScheme::standard())
+    }
+    pub fn with_scheme(
+        rucksack_items: &str,
+        scheme: &PriorityScheme,
+    ) -> Result<Self, CompartmentError> {
+        let rucksack_items = rucksack_items.trim();
+        let length = rucksack_items.chars().count();
+        if !length.is_multiple_of(2) {
+            return Err(CompartmentError::OddLength(length));
+        }
+        let split = rucksack_items
+            .char_indices()
+            .nth(length / 2)
+            .map_or(rucksack_items.len(), |(byte_index, _)| byte_index);
+        let (first, second) = rucksack_items.split_at(split);
+        let first = Compartment::new(first, 0, scheme)?;
+        let second = Compartment::new(second, length / 2, scheme)?;
+        let shared_types = first.item_types.intersection(&second.item_types);
+        let shared = shared_types
+            .iter()
+            .map(|index| SharedItem {
+                item: scheme.item(index),
+                first_count: first.count(index),
+                second_count: second.count(index),
+            })
+            .collect();
+        let only_in_first = scheme
+            .items(&first.item_types.difference(&second.item_types))
+            .collect();
+        let only_in_second = scheme
+            .items(&second.item_types.difference(&first.item_types))
+            .collect();
+        // The shared item that shows up first in the first compartment
+        let first_shared = first.items.chars().find(|current_item| {
+            scheme
+                .index(*current_item)
+                .is_some_and(|index| shared_types.contains(index))
+        });
+        Ok(Self {
+            first,
+            second,
+            shared,
+            only_in_first,
+            only_in_second,
+            first_shared,
+        })
+    }
+    pub fn first(&self) -> &Compartment {
+        &self.first
+    }
+    pub fn second(&self) -> &Compartment {
+        &self.second
+    }
+    pub fn shared_types(&self) -> ItemSet {
+        self.first.item_types.intersection(&self.second.item_types)
+    }
+    // In the scheme's order
+    pub fn shared(&self) -> &[SharedItem] {
+        &self.shared
+    }
+    pub fn only_in_first(&self) -> &[char] {
+        &self.only_in_first
+    }
+    pub fn only_in_second(&self) -> &[char] {
+        &self.only_in_second
+    }
+    pub fn first_shared(&self) -> Option<char> {
+        self.first_shared
+    }
+}
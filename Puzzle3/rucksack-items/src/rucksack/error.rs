@@ -0,0 +1,146 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompartmentError {
+    // Both compartments must hold the same number of items
+    OddLength(usize),
+    // position starts at 1
+    InvalidItem { item: char, position: usize },
+}
+
+impl fmt::Display for CompartmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLength(length) => write!(
+                f,
+                "{} items can't be split into two equal compartments",
+                length
+            ),
+            Self::InvalidItem { item, position } => {
+                write!(f, "'{}' at position {} is not an item type", item, position)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferenceError {
+    // The rucksack count isn't a multiple of the group size
+    UnevenGroups { rucksacks: usize, group_size: usize },
+    NoPartition,
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnevenGroups {
+                rucksacks,
+                group_size,
+            } => write!(
+                f,
+                "{} rucksacks can't be split into groups of {}",
+                rucksacks, group_size
+            ),
+            Self::NoPartition => write!(
+                f,
+                "No way to group the rucksacks so every group shares exactly one item type"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriorityError {
+    UnableToLoad(String),
+    // line starts at 1
+    InvalidLine { line: usize, reason: String },
+}
+
+impl fmt::Display for PriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnableToLoad(e) => write!(f, "Unable to load priority scheme: {}", e),
+            Self::InvalidLine { line, reason } => {
+                write!(f, "Invalid priority scheme at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepackError {
+    // A compartment holds half the items, so no item type can have more than that
+    TooManyOfOne {
+        item: char,
+        count: usize,
+        half: usize,
+    },
+    NoBalancedSplit {
+        half: usize,
+    },
+}
+
+impl fmt::Display for RepackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyOfOne { item, count, half } => write!(
+                f,
+                "{} '{}' items don't fit in a compartment of {}",
+                count, item, half
+            ),
+            Self::NoBalancedSplit { half } => write!(
+                f,
+                "the item types can't be split into two compartments of {} items",
+                half
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RucksackError {
+    UnableToLoad(String),
+    // line starts at 1
+    InvalidRucksack {
+        line: usize,
+        error: CompartmentError,
+    },
+    Ungrouped {
+        line: usize,
+        reason: String,
+    },
+    NoBadge {
+        lines: Vec<usize>,
+    },
+}
+
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnableToLoad(e) => write!(f, "Unable to load rucksacks: {}", e),
+            Self::InvalidRucksack { line, error } => {
+                write!(f, "Invalid rucksack at line {}: {}", line, error)
+            }
+            Self::Ungrouped { line, reason } => {
+                write!(f, "Can't group the rucksack at line {}: {}", line, reason)
+            }
+            Self::NoBadge { lines } => {
+                let lines = lines
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "The group at lines {} shares no item type",
+                    lines.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for RucksackError {
+    fn from(e: std::io::Error) -> Self {
+        RucksackError::UnableToLoad(e.to_string())
+    }
+}
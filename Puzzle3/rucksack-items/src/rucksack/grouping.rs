@@ -0,0 +1,137 @@
+use super::error::RucksackError;
+use super::loader::RucksackLine;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Splits the rucksack lines into the groups that share a badge, along with an Ungrouped error for
+// every line that ends up in no group
+pub trait GroupingStrategy {
+    fn group(&self, lines: &[RucksackLine]) -> (Vec<Vec<RucksackLine>>, Vec<RucksackError>);
+    // Why a line can't be grouped at all, lines that pass are the ones handed to group
+    fn check(&self, _line: &RucksackLine) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Every N lines form a group, the lines of a trailing incomplete group are reported
+pub struct Consecutive(pub usize);
+
+impl GroupingStrategy for Consecutive {
+    fn group(&self, lines: &[RucksackLine]) -> (Vec<Vec<RucksackLine>>, Vec<RucksackError>) {
+        let chunks = lines.chunks_exact(self.0);
+        let leftover = chunks.remainder();
+        let ungrouped = leftover
+            .iter()
+            .map(|line| RucksackError::Ungrouped {
+                line: line.line,
+                reason: format!(
+                    "the last group only has {} of {} rucksacks",
+                    leftover.len(),
+                    self.0
+                ),
+            })
+            .collect();
+        (chunks.map(|chunk| chunk.to_vec()).collect(), ungrouped)
+    }
+}
+
+// Every run of N neighbouring lines is a group, so each line belongs to up to N groups. With fewer
+// than N lines there's no group at all
+pub struct SlidingWindow(pub usize);
+
+impl GroupingStrategy for SlidingWindow {
+    fn group(&self, lines: &[RucksackLine]) -> (Vec<Vec<RucksackLine>>, Vec<RucksackError>) {
+        let ungrouped = if lines.len() < self.0 {
+            lines
+                .iter()
+                .map(|line| RucksackError::Ungrouped {
+                    line: line.line,
+                    reason: format!("only {} rucksacks for a window of {}", lines.len(), self.0),
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let groups = lines
+            .windows(self.0)
+            .map(|window| window.to_vec())
+            .collect();
+        (groups, ungrouped)
+    }
+}
+
+// Lines look like "<group id> <items>", groups come out in the order their id first shows up
+pub struct GroupIds;
+
+impl GroupingStrategy for GroupIds {
+    fn group(&self, lines: &[RucksackLine]) -> (Vec<Vec<RucksackLine>>, Vec<RucksackError>) {
+        let mut groups: Vec<Vec<RucksackLine>> = vec![];
+        let mut positions = HashMap::new();
+        for line in lines.iter() {
+            let Some((group_id, items)) = line.items.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let position = *positions.entry(group_id.to_string()).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[position].push(RucksackLine {
+                line: line.line,
+                items: items.trim().to_string(),
+            });
+        }
+        // check already turned down the lines without an id
+        (groups, vec![])
+    }
+    fn check(&self, line: &RucksackLine) -> Result<(), String> {
+        match line.items.split_once(char::is_whitespace) {
+            Some(_) => Ok(()),
+            None => Err(String::from("expected a group id before the items")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupingSpec {
+    Consecutive(usize),
+    SlidingWindow(usize),
+    GroupIds,
+}
+
+impl GroupingSpec {
+    pub fn build(&self) -> Box<dyn GroupingStrategy> {
+        match self {
+            Self::Consecutive(size) => Box::new(Consecutive(*size)),
+            Self::SlidingWindow(size) => Box::new(SlidingWindow(*size)),
+            Self::GroupIds => Box::new(GroupIds),
+        }
+    }
+}
+
+// consecutive:<size>, sliding:<size> or ids
+impl FromStr for GroupingSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (kind, argument) = match spec.split_once(':') {
+            Some((kind, argument)) => (kind, Some(argument)),
+            None => (spec, None),
+        };
+        let size = |argument: Option<&str>| match argument {
+            None => Ok(3),
+            Some(size) => match size.parse::<usize>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!("Invalid group size: {}", size)),
+            },
+        };
+        match (kind, argument) {
+            ("consecutive", argument) => size(argument).map(Self::Consecutive),
+            ("sliding", argument) => size(argument).map(Self::SlidingWindow),
+            ("ids", None) => Ok(Self::GroupIds),
+            _ => Err(format!(
+                "Unknown grouping {}, expected consecutive:<size>, sliding:<size> or ids",
+                spec
+            )),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use super::error::InferenceError;
+use super::item::{ItemSet, PriorityScheme};
+use super::BadgeGroup;
+
+pub const GROUP_SIZE: usize = 3;
+
+pub struct InferredGroup {
+    // Positions of the members in the rucksack list
+    members: [usize; GROUP_SIZE],
+    badge_group: BadgeGroup,
+}
+
+impl InferredGroup {
+    pub fn members(&self) -> [usize; GROUP_SIZE] {
+        self.members
+    }
+    pub fn badge_group(&self) -> &BadgeGroup {
+        &self.badge_group
+    }
+}
+
+// Every triple whose rucksacks share exactly one item type is a candidate group, the solver
+// picks candidates so each rucksack is used once (an exact cover)
+pub struct BadgeSolver {
+    candidates: Vec<[usize; GROUP_SIZE]>,
+    // Candidate indices each rucksack takes part in
+    candidates_by_rucksack: Vec<Vec<usize>>,
+}
+
+impl BadgeSolver {
+    pub fn new<S: AsRef<str>>(rucksacks: &[S], scheme: &PriorityScheme) -> Self {
+        let item_sets = rucksacks
+            .iter()
+            .map(|rucksack| ItemSet::from_items(rucksack.as_ref(), scheme))
+            .collect::<Vec<_>>();
+        let mut candidates = vec![];
+        let mut candidates_by_rucksack = vec![vec![]; rucksacks.len()];
+        for first in 0..item_sets.len() {
+            for second in first + 1..item_sets.len() {
+                let shared = item_sets[first].intersection(&item_sets[second]);
+                if shared.is_empty() {
+                    continue;
+                }
+                for (third, item_set) in item_sets.iter().enumerate().skip(second + 1) {
+                    if shared.intersection_len(item_set) != 1 {
+                        continue;
+                    }
+                    for member in [first, second, third] {
+                        candidates_by_rucksack[member].push(candidates.len());
+                    }
+                    candidates.push([first, second, third]);
+                }
+            }
+        }
+        Self {
+            candidates,
+            candidates_by_rucksack,
+        }
+    }
+
+    pub fn solve(&self) -> Option<Vec<[usize; GROUP_SIZE]>> {
+        let mut search = Search {
+            grouped: vec![false; self.candidates_by_rucksack.len()],
+            taken_members: vec![0; self.candidates.len()],
+            open_candidates: self.candidates_by_rucksack.iter().map(Vec::len).collect(),
+            chosen: vec![],
+        };
+        if search.run(self) {
+            Some(
+                search
+                    .chosen
+                    .iter()
+                    .map(|candidate| self.candidates[*candidate])
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+struct Search {
+    grouped: Vec<bool>,
+    // How many members of each candidate are already in a chosen group
+    taken_members: Vec<usize>,
+    // How many candidates each rucksack could still be grouped with
+    open_candidates: Vec<usize>,
+    chosen: Vec<usize>,
+}
+
+impl Search {
+    fn choose(&mut self, solver: &BadgeSolver, candidate: usize) {
+        for member in solver.candidates[candidate] {
+            self.grouped[member] = true;
+            for blocked in solver.candidates_by_rucksack[member].iter() {
+                self.taken_members[*blocked] += 1;
+                if self.taken_members[*blocked] == 1 {
+                    for other in solver.candidates[*blocked] {
+                        self.open_candidates[other] -= 1;
+                    }
+                }
+            }
+        }
+        self.chosen.push(candidate);
+    }
+
+    fn unchoose(&mut self, solver: &BadgeSolver) {
+        let candidate = self
+            .chosen
+            .pop()
+            .expect("Only chosen candidates are undone");
+        for member in solver.candidates[candidate] {
+            self.grouped[member] = false;
+            for blocked in solver.candidates_by_rucksack[member].iter() {
+                self.taken_members[*blocked] -= 1;
+                if self.taken_members[*blocked] == 0 {
+                    for other in solver.candidates[*blocked] {
+                        self.open_candidates[other] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn run(&mut self, solver: &BadgeSolver) -> bool {
+        // The rucksack with the fewest candidates left goes first, so dead ends show up early
+        let Some(rucksack) = (0..self.grouped.len())
+            .filter(|rucksack| !self.grouped[*rucksack])
+            .min_by_key(|rucksack| self.open_candidates[*rucksack])
+        else {
+            return true;
+        };
+        let available = solver.candidates_by_rucksack[rucksack]
+            .iter()
+            .copied()
+            .filter(|candidate| self.taken_members[*candidate] == 0)
+            .collect::<Vec<_>>();
+        for candidate in available {
+            self.choose(solver, candidate);
+            if self.run(solver) {
+                return true;
+            }
+            self.unchoose(solver);
+        }
+        false
+    }
+}
+
+pub fn infer_badge_groups<S: AsRef<str>>(
+    rucksacks: &[S],
+    scheme: &PriorityScheme,
+) -> Result<Vec<InferredGroup>, InferenceError> {
+    if !rucksacks.len().is_multiple_of(GROUP_SIZE) {
+        return Err(InferenceError::UnevenGroups {
+            rucksacks: rucksacks.len(),
+            group_size: GROUP_SIZE,
+        });
+    }
+    let groups = BadgeSolver::new(rucksacks, scheme)
+        .solve()
+        .ok_or(InferenceError::NoPartition)?;
+    Ok(groups
+        .into_iter()
+        .map(|members| InferredGroup {
+            members,
+            badge_group: BadgeGroup::with_scheme(
+                &members.map(|member| rucksacks[member].as_ref()),
+                scheme,
+            )
+            .expect("Candidate groups share an item type"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example from the puzzle, it splits into the groups of the puzzle but also into others
+    const SAMPLE: [&str; 6] = [
+        "vJrwpWtwJgWrhcsFMMfFFhFp",
+        "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+        "PmmdzqPrVvPwwTWBwg",
+        "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+        "ttgJtRGJQctTZtZT",
+        "CrZsJsPPZsGzwwsLwLmpwMDw",
+    ];
+
+    fn shared_types(rucksacks: &[&str], members: [usize; GROUP_SIZE]) -> usize {
+        let scheme = PriorityScheme::standard();
+        members
+            .iter()
+            .map(|member| ItemSet::from_items(rucksacks[*member], scheme))
+            .reduce(|shared, item_set| shared.intersection(&item_set))
+            .map_or(0, |shared| shared.len())
+    }
+
+    #[test]
+    fn test_solve_shuffled_sample() {
+        let rucksacks = [3, 0, 4, 1, 5, 2].map(|line| SAMPLE[line]);
+        let groups = BadgeSolver::new(&rucksacks, PriorityScheme::standard())
+            .solve()
+            .unwrap();
+        assert_eq!(2, groups.len());
+        let mut members = groups.iter().flatten().copied().collect::<Vec<_>>();
+        members.sort();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], members);
+        for group in groups {
+            assert_eq!(1, shared_types(&rucksacks, group));
+        }
+    }
+    #[test]
+    fn test_solve_unique_partition() {
+        // Any three of the rucksacks with b share it, but only one of those triples leaves the
+        // three with a for the other group
+        let rucksacks = ["ax", "by", "bz", "aw", "bv", "abu"];
+        let mut groups = BadgeSolver::new(&rucksacks, PriorityScheme::standard())
+            .solve()
+            .unwrap();
+        groups.sort();
+        assert_eq!(vec![[0, 3, 5], [1, 2, 4]], groups);
+    }
+    #[test]
+    fn test_infer_shuffled_sample() {
+        let rucksacks = [5, 2, 1, 3, 0, 4].map(|line| SAMPLE[line]);
+        let groups = infer_badge_groups(&rucksacks, PriorityScheme::standard()).unwrap();
+        assert_eq!(2, groups.len());
+        for group in groups.iter() {
+            let badge = group.badge_group().repeated_char();
+            assert!(group
+                .members()
+                .iter()
+                .all(|member| rucksacks[*member].contains(badge)));
+        }
+    }
+    #[test]
+    fn test_no_partition() {
+        let rucksacks = ["abcd", "cdef", "efgh"];
+        assert_eq!(
+            None,
+            BadgeSolver::new(&rucksacks, PriorityScheme::standard()).solve()
+        );
+        assert_eq!(
+            Some(InferenceError::NoPartition),
+            infer_badge_groups(&rucksacks, PriorityScheme::standard()).err()
+        );
+        // "ab", "ac" and "ad" share only a, but then nothing is left for the other three
+        let rucksacks = ["ab", "ac", "ad", "ef", "gh", "ij"];
+        assert_eq!(
+            Some(InferenceError::NoPartition),
+            infer_badge_groups(&rucksacks, PriorityScheme::standard()).err()
+        );
+    }
+    #[test]
+    fn test_uneven_groups() {
+        assert_eq!(
+            Some(InferenceError::UnevenGroups {
+                rucksacks: 4,
+                group_size: GROUP_SIZE
+            }),
+            infer_badge_groups(&SAMPLE[..4], PriorityScheme::standard()).err()
+        );
+    }
+}
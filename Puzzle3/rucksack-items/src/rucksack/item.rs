@@ -1,15 +1,245 @@
-pub fn get_item_value(item: char) -> u32 {
-    if item.is_uppercase() {
-        get_upper_case_value(item)
-    } else {
-        get_lowercase_value(item)
+use super::error::PriorityError;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+// The item types a rucksack can hold and what each one is worth. Item types get an index in the
+// order they were defined, so an ItemSet can keep one bit per type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorityScheme {
+    items: Vec<char>,
+    priorities: Vec<u32>,
+    indices: HashMap<char, usize>,
+}
+
+static STANDARD_SCHEME: OnceLock<PriorityScheme> = OnceLock::new();
+
+const STANDARD_CONFIG: &str = "\
+alphabet abcdefghijklmnopqrstuvwxyz
+alphabet ABCDEFGHIJKLMNOPQRSTUVWXYZ
+";
+
+// The standard letters followed by the Latin-1 lowercase and then uppercase letters
+const LATIN1_CONFIG: &str = "\
+preset standard
+alphabet ßàáâãäåæçèéêëìíîïðñòóôõöøùúûüýþÿ
+alphabet ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖØÙÚÛÜÝÞ
+";
+
+pub const PRESETS: [&str; 2] = ["standard", "latin1"];
+
+impl PriorityScheme {
+    // a-z are worth 1 to 26 and A-Z 27 to 52
+    pub fn standard() -> &'static PriorityScheme {
+        STANDARD_SCHEME
+            .get_or_init(|| Self::parse(STANDARD_CONFIG).expect("Invalid standard scheme"))
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::standard().clone()),
+            "latin1" => Some(Self::parse(LATIN1_CONFIG).expect("Invalid latin1 scheme")),
+            _ => None,
+        }
+    }
+
+    // A preset name, or else the file to load the scheme from
+    pub fn from_preset_or_file(name: &str) -> Result<Self, PriorityError> {
+        match Self::preset(name) {
+            Some(scheme) => Ok(scheme),
+            None => Self::load(name),
+        }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, PriorityError> {
+        let config = fs::read_to_string(filename)
+            .map_err(|e| PriorityError::UnableToLoad(format!("{}: {}", filename, e)))?;
+        Self::parse(&config)
+    }
+
+    // Starts out empty, one definition per line, # starts a comment:
+    //   preset <name>             replaces everything with the preset
+    //   alphabet <items>          each item is worth one more than the most valuable so far
+    //   item <item> <priority>
+    // Redefining an item only changes its priority
+    pub fn parse(config: &str) -> Result<Self, PriorityError> {
+        let mut scheme = Self {
+            items: vec![],
+            priorities: vec![],
+            indices: HashMap::new(),
+        };
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let invalid_line = |reason: String| PriorityError::InvalidLine {
+                line: line_index + 1,
+                reason,
+            };
+            match words.as_slice() {
+                [] => {}
+                ["preset", name] => {
+                    scheme = Self::preset(name)
+                        .ok_or_else(|| invalid_line(format!("no such preset: {}", name)))?;
+                }
+                ["alphabet", alphabet @ ..] if !alphabet.is_empty() => {
+                    for item in alphabet.iter().flat_map(|letters| letters.chars()) {
+                        let priority = scheme.priorities.iter().max().map_or(1, |max| max + 1);
+                        scheme.define(item, priority);
+                    }
+                }
+                ["item", item, priority] => {
+                    let mut chars = item.chars();
+                    let (Some(item), None) = (chars.next(), chars.next()) else {
+                        return Err(invalid_line(format!(
+                            "an item is a single character, found {}",
+                            item
+                        )));
+                    };
+                    let priority = priority
+                        .parse::<u32>()
+                        .map_err(|_| invalid_line(format!("invalid priority: {}", priority)))?;
+                    scheme.define(item, priority);
+                }
+                _ => return Err(invalid_line(format!("unknown definition: {}", line.trim()))),
+            }
+        }
+        Ok(scheme)
+    }
+
+    fn define(&mut self, item: char, priority: u32) {
+        match self.indices.get(&item) {
+            Some(index) => self.priorities[*index] = priority,
+            None => {
+                self.indices.insert(item, self.items.len());
+                self.items.push(item);
+                self.priorities.push(priority);
+            }
+        }
+    }
+
+    pub fn index(&self, item: char) -> Option<usize> {
+        self.indices.get(&item).copied()
+    }
+
+    pub fn item(&self, index: usize) -> char {
+        self.items[index]
+    }
+
+    pub fn priority(&self, item: char) -> Option<u32> {
+        self.index(item).map(|index| self.priorities[index])
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // In the order the item types were defined
+    pub fn items<'a>(&'a self, item_types: &'a ItemSet) -> impl Iterator<Item = char> + 'a {
+        item_types.iter().map(|index| self.item(index))
     }
 }
 
-fn get_lowercase_value(item: char) -> u32 {
-    item as u32 - 'a' as u32 + 1
+impl Default for PriorityScheme {
+    fn default() -> Self {
+        Self::standard().clone()
+    }
 }
 
-fn get_upper_case_value(item: char) -> u32 {
-    item as u32 - 'A' as u32 + 27
+// One bit per item type index, so set operations on whole rucksacks work a word at a time
+#[derive(Debug, Clone, Default)]
+pub struct ItemSet {
+    words: Vec<u64>,
+}
+
+impl ItemSet {
+    pub fn new() -> Self {
+        Self { words: vec![] }
+    }
+    // Characters the scheme doesn't know are left out
+    pub fn from_items(items: &str, scheme: &PriorityScheme) -> Self {
+        let mut item_types = Self::new();
+        for index in items.chars().filter_map(|item| scheme.index(item)) {
+            item_types.insert(index);
+        }
+        item_types
+    }
+    pub fn insert(&mut self, index: usize) {
+        if self.words.len() <= index / 64 {
+            self.words.resize(index / 64 + 1, 0);
+        }
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+    pub fn remove(&mut self, index: usize) {
+        if let Some(word) = self.words.get_mut(index / 64) {
+            *word &= !(1 << (index % 64));
+        }
+    }
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & b)
+                .collect(),
+        }
+    }
+    // The size of the intersection, without building it
+    pub fn intersection_len(&self, other: &ItemSet) -> usize {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        let (longer, shorter) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = longer.words.clone();
+        words
+            .iter_mut()
+            .zip(shorter.words.iter())
+            .for_each(|(a, b)| *a |= b);
+        Self { words }
+    }
+    pub fn difference(&self, other: &ItemSet) -> ItemSet {
+        let mut words = self.words.clone();
+        words
+            .iter_mut()
+            .zip(other.words.iter())
+            .for_each(|(a, b)| *a &= !b);
+        Self { words }
+    }
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+    // Indices come out in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index * 64 + bit)
+            })
+    }
 }
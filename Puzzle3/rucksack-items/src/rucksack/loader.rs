@@ -1,37 +1,209 @@
+use super::error::{CompartmentError, RucksackError};
+use super::grouping::{Consecutive, GroupingStrategy};
+use super::item::PriorityScheme;
 use super::{BadgeGroup, Rucksack};
-use std::fs;
-pub fn load_rucksack(filename: &str) -> Vec<Rucksack> {
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    // Stops at the first malformed rucksack
+    Strict,
+    // Skips malformed rucksacks and returns their errors along with everything else
+    Lenient,
+}
+
+// A non blank input line, line starts at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RucksackLine {
+    pub line: usize,
+    pub items: String,
+}
+
+// Records a malformed rucksack, or in strict mode gives up on the whole file
+fn skip(
+    error: RucksackError,
+    mode: LoadMode,
+    skipped: &mut Vec<RucksackError>,
+) -> Result<(), RucksackError> {
+    match mode {
+        LoadMode::Strict => Err(error),
+        LoadMode::Lenient => {
+            skipped.push(error);
+            Ok(())
+        }
+    }
+}
+
+fn open(filename: &str) -> Result<BufReader<File>, RucksackError> {
+    let file = File::open(filename)
+        .map_err(|e| RucksackError::UnableToLoad(format!("{}: {}", filename, e)))?;
+    Ok(BufReader::new(file))
+}
+
+pub fn load_lines_from<R: BufRead>(reader: R) -> Result<Vec<RucksackLine>, RucksackError> {
+    let mut lines = vec![];
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        lines.push(RucksackLine {
+            line: line_index + 1,
+            items: line.trim().to_string(),
+        });
+    }
+    Ok(lines)
+}
+
+pub fn load_lines(filename: &str) -> Result<Vec<RucksackLine>, RucksackError> {
+    load_lines_from(open(filename)?)
+}
+
+pub fn load_rucksacks_from<R: BufRead>(
+    reader: R,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<Rucksack>, Vec<RucksackError>), RucksackError> {
     let mut rucksacks = vec![];
-    let file_content = fs::read_to_string(filename).unwrap();
-    for line in file_content.lines() {
-        if let Some(rucksack) = Rucksack::new(line) {
-            rucksacks.push(rucksack);
+    let mut skipped = vec![];
+    for line in load_lines_from(reader)? {
+        match Rucksack::with_scheme(&line.items, scheme) {
+            Ok(rucksack) => rucksacks.push(rucksack),
+            Err(error) => skip(
+                RucksackError::InvalidRucksack {
+                    line: line.line,
+                    error,
+                },
+                mode,
+                &mut skipped,
+            )?,
         }
     }
-    rucksacks
+    Ok((rucksacks, skipped))
+}
+
+pub fn load_rucksack(
+    filename: &str,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<Rucksack>, Vec<RucksackError>), RucksackError> {
+    load_rucksacks_from(open(filename)?, scheme, mode)
 }
-pub fn load_badgegroup(filename: &str) -> Vec<BadgeGroup> {
+
+// Badge groups don't need even rucksacks, only items the scheme knows
+fn check_items(line: &RucksackLine, scheme: &PriorityScheme) -> Result<(), RucksackError> {
+    match line
+        .items
+        .chars()
+        .enumerate()
+        .find(|(_, item)| scheme.index(*item).is_none())
+    {
+        Some((position, item)) => Err(RucksackError::InvalidRucksack {
+            line: line.line,
+            error: CompartmentError::InvalidItem {
+                item,
+                position: position + 1,
+            },
+        }),
+        None => Ok(()),
+    }
+}
+
+// Lines made only of items the scheme knows, for when rucksacks are grouped later on
+pub fn load_item_lines_from<R: BufRead>(
+    reader: R,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<RucksackLine>, Vec<RucksackError>), RucksackError> {
+    let mut lines = vec![];
+    let mut skipped = vec![];
+    for line in load_lines_from(reader)? {
+        match check_items(&line, scheme) {
+            Ok(()) => lines.push(line),
+            Err(error) => skip(error, mode, &mut skipped)?,
+        }
+    }
+    Ok((lines, skipped))
+}
+
+pub fn load_item_lines(
+    filename: &str,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<RucksackLine>, Vec<RucksackError>), RucksackError> {
+    load_item_lines_from(open(filename)?, scheme, mode)
+}
+
+// A malformed member drops its whole group rather than the line, so the groups after it keep
+// their members. Lines left out of every group are skipped like any other invalid line
+pub fn load_badgegroups_from<R: BufRead>(
+    reader: R,
+    grouping: &dyn GroupingStrategy,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<BadgeGroup>, Vec<RucksackError>), RucksackError> {
+    let mut lines = vec![];
+    let mut skipped = vec![];
+    for line in load_lines_from(reader)? {
+        match grouping.check(&line) {
+            Ok(()) => lines.push(line),
+            Err(reason) => skip(
+                RucksackError::Ungrouped {
+                    line: line.line,
+                    reason,
+                },
+                mode,
+                &mut skipped,
+            )?,
+        }
+    }
+    let (groups, ungrouped) = grouping.group(&lines);
     let mut badgegroups = vec![];
-    let file_content = fs::read_to_string(filename).unwrap();
-    let mut count = 0;
-    let mut group = vec![];
-    for line in file_content.lines() {
-        if count < 3 {
-            group.push(line.trim());
-            count += 1;
-        } else {
-            if let Some(badgegroup) = BadgeGroup::new(group[0], group[1], group[2]) {
-                badgegroups.push(badgegroup);
+    'groups: for group in groups {
+        for member in group.iter() {
+            if let Err(error) = check_items(member, scheme) {
+                skip(error, mode, &mut skipped)?;
+                continue 'groups;
             }
-            count = 1;
-            group.clear();
-            group.push(line.trim());
         }
-    }
-    if count >= 3 {
-        if let Some(badgegroup) = BadgeGroup::new(group[0], group[1], group[2]) {
-            badgegroups.push(badgegroup);
+        let members = group
+            .iter()
+            .map(|member| member.items.as_str())
+            .collect::<Vec<_>>();
+        match BadgeGroup::with_scheme(&members, scheme) {
+            Some(badgegroup) => badgegroups.push(badgegroup),
+            None => skip(
+                RucksackError::NoBadge {
+                    lines: group.iter().map(|member| member.line).collect(),
+                },
+                mode,
+                &mut skipped,
+            )?,
         }
     }
-    badgegroups
+    for error in ungrouped {
+        skip(error, mode, &mut skipped)?;
+    }
+    Ok((badgegroups, skipped))
+}
+
+pub fn load_badgegroups(
+    filename: &str,
+    grouping: &dyn GroupingStrategy,
+    scheme: &PriorityScheme,
+    mode: LoadMode,
+) -> Result<(Vec<BadgeGroup>, Vec<RucksackError>), RucksackError> {
+    load_badgegroups_from(open(filename)?, grouping, scheme, mode)
+}
+
+pub fn load_badgegroup(
+    filename: &str,
+) -> Result<(Vec<BadgeGroup>, Vec<RucksackError>), RucksackError> {
+    load_badgegroups(
+        filename,
+        &Consecutive(3),
+        PriorityScheme::standard(),
+        LoadMode::Lenient,
+    )
 }
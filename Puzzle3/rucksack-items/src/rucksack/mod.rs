@@ -1,34 +1,43 @@
-mod item;
+pub mod compartment;
+pub mod error;
+pub mod grouping;
+pub mod inference;
+pub mod item;
 pub mod loader;
+pub mod repack;
+
+use compartment::CompartmentAnalysis;
+use error::CompartmentError;
+use item::{ItemSet, PriorityScheme};
 
 pub struct Rucksack {
-    repeated_char: char,
+    analysis: CompartmentAnalysis,
+    repeated_char: Option<char>,
     item_priority: u32,
 }
 
 impl Rucksack {
-    pub fn new(rucksack_items: &str) -> Option<Self> {
-        // todo: we're assuming all chars are ascii
-        // todo: handle border cases
-        let rucksack_size = rucksack_items.trim().len() / 2;
-        let second_compartment = &rucksack_items.trim()[rucksack_size..];
-        let chars = rucksack_items.trim().chars();
-        let mut count = 0;
-        for current_char in chars.into_iter() {
-            if count >= rucksack_size {
-                break;
-            }
-            if second_compartment.contains(current_char) {
-                return Some(Self {
-                    repeated_char: current_char,
-                    item_priority: item::get_item_value(current_char),
-                });
-            }
-            count += 1;
-        }
-        None
+    pub fn new(rucksack_items: &str) -> Result<Self, CompartmentError> {
+        Self::with_scheme(rucksack_items, PriorityScheme::standard())
     }
-    pub fn repeated_char(&self) -> char {
+    pub fn with_scheme(
+        rucksack_items: &str,
+        scheme: &PriorityScheme,
+    ) -> Result<Self, CompartmentError> {
+        let analysis = CompartmentAnalysis::with_scheme(rucksack_items, scheme)?;
+        let repeated_char = analysis.first_shared();
+        Ok(Self {
+            analysis,
+            repeated_char,
+            item_priority: repeated_char
+                .and_then(|item| scheme.priority(item))
+                .unwrap_or_default(),
+        })
+    }
+    pub fn analysis(&self) -> &CompartmentAnalysis {
+        &self.analysis
+    }
+    pub fn repeated_char(&self) -> Option<char> {
         self.repeated_char
     }
     pub fn points(&self) -> u32 {
@@ -37,24 +46,50 @@ impl Rucksack {
 }
 
 pub struct BadgeGroup {
+    members: Vec<String>,
+    common_items: Vec<char>,
     repeated_char: char,
     item_priority: u32,
 }
 impl BadgeGroup {
-    pub fn new(elf1: &str, elf2: &str, elf3: &str) -> Option<Self> {
-        for item in elf1.chars().into_iter() {
-            if elf2.contains(item) && elf3.contains(item) {
-                return Some(Self {
-                    repeated_char: item,
-                    item_priority: item::get_item_value(item),
-                });
-            }
-        }
-        None
+    pub fn new<S: AsRef<str>>(members: &[S]) -> Option<Self> {
+        Self::with_scheme(members, PriorityScheme::standard())
+    }
+    // Any number of elves, the badge is the first common item carried by the first elf
+    pub fn with_scheme<S: AsRef<str>>(members: &[S], scheme: &PriorityScheme) -> Option<Self> {
+        let (first, others) = members.split_first()?;
+        let common_types = others.iter().fold(
+            ItemSet::from_items(first.as_ref(), scheme),
+            |common, member| common.intersection(&ItemSet::from_items(member.as_ref(), scheme)),
+        );
+        let repeated_char = first.as_ref().chars().find(|current_item| {
+            scheme
+                .index(*current_item)
+                .is_some_and(|index| common_types.contains(index))
+        })?;
+        Some(Self {
+            members: members
+                .iter()
+                .map(|member| member.as_ref().to_string())
+                .collect(),
+            common_items: scheme.items(&common_types).collect(),
+            repeated_char,
+            item_priority: scheme.priority(repeated_char).unwrap_or_default(),
+        })
+    }
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+    pub fn size(&self) -> usize {
+        self.members.len()
     }
     pub fn repeated_char(&self) -> char {
         self.repeated_char
     }
+    // Every item type all the members carry, in the scheme's order
+    pub fn common_items(&self) -> &[char] {
+        &self.common_items
+    }
     pub fn points(&self) -> u32 {
         self.item_priority
     }
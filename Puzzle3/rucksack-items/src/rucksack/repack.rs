@@ -0,0 +1,253 @@
+use super::compartment::CompartmentAnalysis;
+use super::error::RepackError;
+use super::item::PriorityScheme;
+
+// Exchanges one item of the first compartment with one of the second, positions start at 1
+// and count from the start of the rucksack like CompartmentError does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub first_position: usize,
+    pub first_item: char,
+    pub second_position: usize,
+    pub second_item: char,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepackPlan {
+    swaps: Vec<Swap>,
+    first: String,
+    second: String,
+}
+
+impl RepackPlan {
+    pub fn swaps(&self) -> &[Swap] {
+        &self.swaps
+    }
+    pub fn first(&self) -> &str {
+        &self.first
+    }
+    pub fn second(&self) -> &str {
+        &self.second
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    First,
+    Second,
+}
+
+// Every item type has to end up whole in one compartment. Sending a type to the first one moves
+// its items out of the second and the other way round, and as swaps keep both halves the same size
+// the items moved each way have to match. A knapsack over the difference between the two finds
+// the cheapest way to balance them, and the number of swaps is the items moved either way
+pub fn plan_repack(
+    analysis: &CompartmentAnalysis,
+    scheme: &PriorityScheme,
+) -> Result<RepackPlan, RepackError> {
+    let first = analysis.first();
+    let second = analysis.second();
+    let half = first.len();
+    let item_types = first.item_types().union(second.item_types());
+    let counts = item_types
+        .iter()
+        .map(|index| {
+            (
+                index,
+                first.count(index) as usize,
+                second.count(index) as usize,
+            )
+        })
+        .collect::<Vec<_>>();
+    if let Some((index, in_first, in_second)) = counts
+        .iter()
+        .find(|(_, in_first, in_second)| in_first + in_second > half)
+    {
+        return Err(RepackError::TooManyOfOne {
+            item: scheme.item(*index),
+            count: in_first + in_second,
+            half,
+        });
+    }
+
+    // cheapest[layer][offset + moved_out - moved_in] is the fewest items moved out of the first
+    // compartment to get that difference after deciding the first `layer` item types
+    let offset = half;
+    let width = 2 * half + 1;
+    let mut cheapest = vec![vec![None; width]; counts.len() + 1];
+    cheapest[0][offset] = Some((0, Side::First));
+    for (layer, (_, in_first, in_second)) in counts.iter().enumerate() {
+        for difference in 0..width {
+            let Some((moved_out, _)) = cheapest[layer][difference] else {
+                continue;
+            };
+            let options = [
+                (Side::First, difference.checked_sub(*in_second), moved_out),
+                (
+                    Side::Second,
+                    Some(difference + in_first),
+                    moved_out + in_first,
+                ),
+            ];
+            for (side, next_difference, next_moved_out) in options {
+                let Some(next_difference) = next_difference.filter(|next| *next < width) else {
+                    continue;
+                };
+                let next = &mut cheapest[layer + 1][next_difference];
+                if next.is_none_or(|(current, _)| next_moved_out < current) {
+                    *next = Some((next_moved_out, side));
+                }
+            }
+        }
+    }
+    if cheapest[counts.len()][offset].is_none() {
+        return Err(RepackError::NoBalancedSplit { half });
+    }
+
+    // Walk back through the choices to find where each item type goes
+    let mut sides = vec![Side::First; counts.len()];
+    let mut difference = offset;
+    for layer in (0..counts.len()).rev() {
+        let (_, side) = cheapest[layer + 1][difference].expect("Reachable states have a choice");
+        let (_, in_first, in_second) = counts[layer];
+        sides[layer] = side;
+        difference = match side {
+            Side::First => difference + in_second,
+            Side::Second => difference - in_first,
+        };
+    }
+    let side_of = |item: char| {
+        let index = scheme
+            .index(item)
+            .expect("Analyzed items are in the scheme");
+        let layer = counts
+            .iter()
+            .position(|(item_index, _, _)| *item_index == index)
+            .expect("Every item type has a side");
+        sides[layer]
+    };
+
+    let mut first_items = first.items().chars().collect::<Vec<_>>();
+    let mut second_items = second.items().chars().collect::<Vec<_>>();
+    let moving_out = (0..half)
+        .filter(|position| side_of(first_items[*position]) == Side::Second)
+        .collect::<Vec<_>>();
+    let moving_in = (0..half)
+        .filter(|position| side_of(second_items[*position]) == Side::First)
+        .collect::<Vec<_>>();
+    let swaps = moving_out
+        .into_iter()
+        .zip(moving_in)
+        .map(|(first_position, second_position)| {
+            let swap = Swap {
+                first_position: first_position + 1,
+                first_item: first_items[first_position],
+                second_position: half + second_position + 1,
+                second_item: second_items[second_position],
+            };
+            first_items[first_position] = swap.second_item;
+            second_items[second_position] = swap.first_item;
+            swap
+        })
+        .collect();
+    Ok(RepackPlan {
+        swaps,
+        first: first_items.into_iter().collect(),
+        second: second_items.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repack(rucksack: &str) -> Result<RepackPlan, RepackError> {
+        let scheme = PriorityScheme::standard();
+        plan_repack(
+            &CompartmentAnalysis::with_scheme(rucksack, scheme).unwrap(),
+            scheme,
+        )
+    }
+
+    // Tries every way of sending each item type to one side, the fewest swaps is the fewest items
+    // the first compartment has to give away
+    fn fewest_swaps(rucksack: &str) -> Option<usize> {
+        let items = rucksack.chars().collect::<Vec<_>>();
+        let first = &items[..items.len() / 2];
+        let mut item_types = items.clone();
+        item_types.sort();
+        item_types.dedup();
+        (0..1u32 << item_types.len())
+            .filter_map(|in_first| {
+                let goes_first = |item: &char| {
+                    let position = item_types.binary_search(item).unwrap();
+                    in_first & (1 << position) != 0
+                };
+                let first_size = items.iter().filter(|item| goes_first(item)).count();
+                (first_size == first.len())
+                    .then(|| first.iter().filter(|item| !goes_first(item)).count())
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_already_split() {
+        let plan = repack("aabb").unwrap();
+        assert_eq!(0, plan.swaps().len());
+        assert_eq!("aa", plan.first());
+        assert_eq!("bb", plan.second());
+    }
+    #[test]
+    fn test_minimal_swaps() {
+        for rucksack in [
+            "abab",
+            "aabcbcdd",
+            "abcdbadc",
+            "aabbcdcdeeff",
+            "abacbcddeeff",
+        ] {
+            let plan = repack(rucksack).unwrap();
+            assert_eq!(fewest_swaps(rucksack).unwrap(), plan.swaps().len());
+            let first = plan.first().chars().collect::<Vec<_>>();
+            assert!(plan.second().chars().all(|item| !first.contains(&item)));
+        }
+        // a and b end up in the first compartment, c and d trade places with them in order
+        let plan = repack("abcdbadc").unwrap();
+        assert_eq!(
+            vec![
+                Swap {
+                    first_position: 3,
+                    first_item: 'c',
+                    second_position: 5,
+                    second_item: 'b'
+                },
+                Swap {
+                    first_position: 4,
+                    first_item: 'd',
+                    second_position: 6,
+                    second_item: 'a'
+                }
+            ],
+            plan.swaps()
+        );
+    }
+    #[test]
+    fn test_too_many_of_one() {
+        assert_eq!(
+            Some(RepackError::TooManyOfOne {
+                item: 'a',
+                count: 3,
+                half: 2
+            }),
+            repack("aaab").err()
+        );
+    }
+    #[test]
+    fn test_no_balanced_split() {
+        assert_eq!(None, fewest_swaps("abcacb"));
+        assert_eq!(
+            Some(RepackError::NoBalancedSplit { half: 3 }),
+            repack("abcacb").err()
+        );
+    }
+}
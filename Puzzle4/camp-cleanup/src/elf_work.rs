@@ -1,20 +1,75 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionRelation {
+    Disjoint,
+    // No section in common, but nothing between them either
+    Adjacent,
+    Overlapping,
+    Contains,
+    Contained,
+    Equal,
+}
+
+impl SectionRelation {
+    pub fn all() -> [SectionRelation; 6] {
+        [
+            Self::Disjoint,
+            Self::Adjacent,
+            Self::Overlapping,
+            Self::Contains,
+            Self::Contained,
+            Self::Equal,
+        ]
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Disjoint => "disjoint",
+            Self::Adjacent => "adjacent",
+            Self::Overlapping => "overlapping",
+            Self::Contains => "contains",
+            Self::Contained => "contained",
+            Self::Equal => "equal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AssignedSections {
     start_index: u32,
     end_index: u32,
 }
 
 impl AssignedSections {
+    // None for anything FromStr turns down, use parse to know why
     pub fn new(work_line: &str) -> Option<Self> {
-        let split_pair = work_line.trim().split("-").collect::<Vec<_>>();
-        if split_pair.len() < 2 {
+        work_line.parse().ok()
+    }
+    // Both ends are included
+    pub fn from_bounds(start_index: u32, end_index: u32) -> Option<Self> {
+        if start_index > end_index {
             return None;
         }
-        // todo: Check error
         Some(Self {
-            start_index: split_pair[0].parse::<u32>().unwrap(),
-            end_index: split_pair[1].parse::<u32>().unwrap(),
+            start_index,
+            end_index,
         })
     }
+    pub fn start(&self) -> u32 {
+        self.start_index
+    }
+    pub fn end(&self) -> u32 {
+        self.end_index
+    }
+    // Number of sections, both ends included
+    // u64 because 0-4294967295 holds one more section than u32 can count
+    pub fn len(&self) -> u64 {
+        (self.end_index as u64 + 1).saturating_sub(self.start_index as u64)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     pub fn fully_contains(&self, other: &AssignedSections) -> bool {
         self.start_index <= other.start_index && self.end_index >= other.end_index
     }
@@ -28,16 +83,93 @@ impl AssignedSections {
             || self.fully_contains(other)
             || other.fully_contains(self)
     }
-    pub fn parse_pair(line: &str) -> Option<(AssignedSections, AssignedSections)> {
-        let pair = line.trim().split(",").collect::<Vec<_>>();
-        if pair.len() < 2 {
+    pub fn is_adjacent(&self, other: &AssignedSections) -> bool {
+        self.end_index.checked_add(1) == Some(other.start_index)
+            || other.end_index.checked_add(1) == Some(self.start_index)
+    }
+    pub fn intersection(&self, other: &AssignedSections) -> Option<AssignedSections> {
+        Self::from_bounds(
+            self.start_index.max(other.start_index),
+            self.end_index.min(other.end_index),
+        )
+    }
+    // Only when the result is a single range, so the two have to overlap or touch
+    pub fn union(&self, other: &AssignedSections) -> Option<AssignedSections> {
+        if !self.overlaps(other) && !self.is_adjacent(other) {
             return None;
         }
-        if let Some(first_elf) = AssignedSections::new(pair[0]) {
-            if let Some(second_elf) = AssignedSections::new(pair[1]) {
-                return Some((first_elf, second_elf));
-            }
+        Self::from_bounds(
+            self.start_index.min(other.start_index),
+            self.end_index.max(other.end_index),
+        )
+    }
+    // The sections of self that other doesn't cover, up to one range on each side of it
+    pub fn difference(&self, other: &AssignedSections) -> Vec<AssignedSections> {
+        if self.intersection(other).is_none() {
+            return vec![*self];
+        }
+        let before = other
+            .start_index
+            .checked_sub(1)
+            .and_then(|end_index| Self::from_bounds(self.start_index, end_index));
+        let after = other
+            .end_index
+            .checked_add(1)
+            .and_then(|start_index| Self::from_bounds(start_index, self.end_index));
+        before.into_iter().chain(after).collect()
+    }
+    // How self relates to other, "contains" means self holds all of other
+    pub fn relation(&self, other: &AssignedSections) -> SectionRelation {
+        if self == other {
+            SectionRelation::Equal
+        } else if self.fully_contains(other) {
+            SectionRelation::Contains
+        } else if other.fully_contains(self) {
+            SectionRelation::Contained
+        } else if self.overlaps(other) {
+            SectionRelation::Overlapping
+        } else if self.is_adjacent(other) {
+            SectionRelation::Adjacent
+        } else {
+            SectionRelation::Disjoint
         }
-        None
+    }
+    // Any number of comma separated assignments, None if one of them is invalid
+    pub fn parse_group(line: &str) -> Option<Vec<AssignedSections>> {
+        line.trim()
+            .split(',')
+            .map(|sections| sections.parse::<AssignedSections>().ok())
+            .collect()
+    }
+    // Exactly two valid assignments, None otherwise
+    pub fn parse_pair(line: &str) -> Option<(AssignedSections, AssignedSections)> {
+        let (first_elf, second_elf) = line.trim().split_once(',')?;
+        Some((first_elf.parse().ok()?, second_elf.parse().ok()?))
+    }
+}
+
+impl fmt::Display for AssignedSections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start_index, self.end_index)
+    }
+}
+
+// <start>-<end>, the start can't come after the end
+impl FromStr for AssignedSections {
+    type Err = String;
+
+    fn from_str(sections: &str) -> Result<Self, Self::Err> {
+        let (start_index, end_index) = sections
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| format!("Expected <start>-<end>, found {}", sections))?;
+        let parse_index = |index: &str| {
+            index
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid section: {}", index))
+        };
+        Self::from_bounds(parse_index(start_index)?, parse_index(end_index)?)
+            .ok_or_else(|| format!("{} ends before it starts", sections))
     }
 }
@@ -0,0 +1,135 @@
+use super::elf_work::AssignedSections;
+
+// Sorted ranges that neither overlap nor touch, so every covered section is in exactly one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<AssignedSections>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+    pub fn from_sections<'a, I: IntoIterator<Item = &'a AssignedSections>>(sections: I) -> Self {
+        let mut sorted = sections
+            .into_iter()
+            .filter(|sections| !sections.is_empty())
+            .copied()
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|sections| (sections.start(), sections.end()));
+        let mut ranges: Vec<AssignedSections> = vec![];
+        for sections in sorted {
+            match ranges
+                .last_mut()
+                .and_then(|last| last.union(&sections).map(|merged| (last, merged)))
+            {
+                Some((last, merged)) => *last = merged,
+                None => ranges.push(sections),
+            }
+        }
+        Self { ranges }
+    }
+    pub fn insert(&mut self, sections: AssignedSections) {
+        let mut ranges = self.ranges.clone();
+        ranges.push(sections);
+        *self = Self::from_sections(ranges.iter());
+    }
+    pub fn ranges(&self) -> &[AssignedSections] {
+        &self.ranges
+    }
+    pub fn contains(&self, section: u32) -> bool {
+        let position = self.ranges.partition_point(|range| range.end() < section);
+        self.ranges
+            .get(position)
+            .is_some_and(|range| range.start() <= section)
+    }
+    // Number of sections covered
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    // The smallest range holding every covered section
+    pub fn span(&self) -> Option<AssignedSections> {
+        let first = self.ranges.first()?;
+        let last = self.ranges.last()?;
+        AssignedSections::from_bounds(first.start(), last.end())
+    }
+    // Sections of within that no range covers
+    pub fn gaps(&self, within: &AssignedSections) -> Vec<AssignedSections> {
+        let mut gaps = vec![];
+        // u64 so the section after u32::MAX still fits
+        let mut next_uncovered = within.start() as u64;
+        for range in self.ranges.iter() {
+            if range.start() > within.end() {
+                break;
+            }
+            if (range.end() as u64) < next_uncovered {
+                continue;
+            }
+            if range.start() as u64 > next_uncovered {
+                gaps.extend(AssignedSections::from_bounds(
+                    next_uncovered as u32,
+                    range.start() - 1,
+                ));
+            }
+            next_uncovered = range.end() as u64 + 1;
+        }
+        if next_uncovered <= within.end() as u64 {
+            gaps.extend(AssignedSections::from_bounds(
+                next_uncovered as u32,
+                within.end(),
+            ));
+        }
+        gaps
+    }
+}
+
+// Runs of sections covered by the same number of assignments, left to right. Sections nobody
+// covers are left out
+pub fn coverage_depths(assignments: &[AssignedSections]) -> Vec<(AssignedSections, usize)> {
+    // An assignment adds one from its start and takes it away after its end, u64 keeps end + 1 in range
+    let mut events = assignments
+        .iter()
+        .filter(|sections| !sections.is_empty())
+        .flat_map(|sections| {
+            [
+                (sections.start() as u64, 1),
+                (sections.end() as u64 + 1, -1),
+            ]
+        })
+        .collect::<Vec<(u64, i64)>>();
+    events.sort();
+    let mut depths = vec![];
+    let mut depth = 0;
+    let mut run_start = 0;
+    for (position, change) in events {
+        if depth > 0 && position > run_start {
+            let run = AssignedSections::from_bounds(run_start as u32, (position - 1) as u32)
+                .expect("Runs start before they end");
+            depths.push((run, depth as usize));
+        }
+        depth += change;
+        run_start = position;
+    }
+    depths
+}
+
+pub fn covered_more_than(assignments: &[AssignedSections], elves: usize) -> IntervalSet {
+    let busy = coverage_depths(assignments)
+        .into_iter()
+        .filter(|(_, depth)| *depth > elves)
+        .map(|(run, _)| run)
+        .collect::<Vec<_>>();
+    IntervalSet::from_sections(busy.iter())
+}
+
+// Sections cleaned more than once, counting every extra elf
+pub fn redundant_work(assignments: &[AssignedSections]) -> u64 {
+    let assigned = assignments
+        .iter()
+        .map(|sections| sections.len())
+        .sum::<u64>();
+    assigned - IntervalSet::from_sections(assignments.iter()).len()
+}
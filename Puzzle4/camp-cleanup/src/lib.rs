@@ -1,2 +1,4 @@
 pub mod elf_work;
+pub mod interval_set;
 pub mod loader;
+pub mod sweep;
@@ -1,6 +1,7 @@
 use super::elf_work::AssignedSections;
 use std::fs;
 
+// Lines that aren't a valid pair are skipped
 pub fn load_assignment_pairs(filename: &str) -> Vec<(AssignedSections, AssignedSections)> {
     let mut assignments = vec![];
     let content = fs::read_to_string(filename).unwrap();
@@ -11,3 +12,26 @@ pub fn load_assignment_pairs(filename: &str) -> Vec<(AssignedSections, AssignedS
     }
     assignments
 }
+
+// The elves on one input line, line starts at 1
+pub struct AssignmentGroup {
+    pub line: usize,
+    pub elves: Vec<AssignedSections>,
+}
+
+pub fn load_assignment_groups(filename: &str) -> Vec<AssignmentGroup> {
+    let mut groups = vec![];
+    let content = fs::read_to_string(filename).unwrap();
+    for (line_index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(elves) = AssignedSections::parse_group(line) {
+            groups.push(AssignmentGroup {
+                line: line_index + 1,
+                elves,
+            });
+        }
+    }
+    groups
+}
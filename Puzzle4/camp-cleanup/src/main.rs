@@ -1,4 +1,7 @@
+use camp_cleanup::elf_work::{AssignedSections, SectionRelation};
+use camp_cleanup::interval_set::{self, IntervalSet};
 use camp_cleanup::loader;
+use camp_cleanup::sweep;
 use clap::Parser;
 
 #[derive(Debug, Parser)]
@@ -6,10 +9,120 @@ use clap::Parser;
 struct Args {
     #[arg(short, long)]
     filename: String,
+    // Count the pairs by how their assignments relate and the sections they share
+    #[arg(short, long)]
+    relations: bool,
+    // Merge every assignment and report gaps, busy sections and redundant work
+    #[arg(short, long)]
+    coverage: bool,
+    // Sections to look for gaps in, defaults to the first to the last assigned section
+    #[arg(short, long, requires = "coverage")]
+    within: Option<AssignedSections>,
+    // Busy sections are covered by more than this many elves
+    #[arg(short, long, default_value_t = 1, requires = "coverage")]
+    more_than: usize,
+    // Lines hold any number of elves, list every pair on a line that overlaps
+    #[arg(short, long, conflicts_with_all = ["relations", "coverage"])]
+    groups: bool,
+    // Compare every elf in the file with every other, not just those on the same line
+    #[arg(short = 'a', long, requires = "groups")]
+    whole_file: bool,
+}
+
+fn format_ranges(ranges: &[AssignedSections]) -> String {
+    if ranges.is_empty() {
+        return String::from("none");
+    }
+    ranges
+        .iter()
+        .map(|range| range.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 fn main() {
     let args = Args::parse();
+    if args.groups {
+        let groups = loader::load_assignment_groups(&args.filename);
+        // Elves are named <line>:<position on the line>
+        let mut teams = groups
+            .iter()
+            .map(|group| {
+                let names = (1..=group.elves.len())
+                    .map(|position| format!("{}:{}", group.line, position))
+                    .collect::<Vec<_>>();
+                (names, group.elves.clone())
+            })
+            .collect::<Vec<_>>();
+        if args.whole_file {
+            let names = teams.iter().flat_map(|(names, _)| names.clone()).collect();
+            let elves = teams.iter().flat_map(|(_, elves)| elves.clone()).collect();
+            teams = vec![(names, elves)];
+        }
+        let mut conflict_count = 0;
+        for (names, elves) in teams.iter() {
+            for conflict in sweep::overlapping_pairs(elves) {
+                println!(
+                    "Elves {} ({}) and {} ({}) both clean {}",
+                    names[conflict.first],
+                    elves[conflict.first],
+                    names[conflict.second],
+                    elves[conflict.second],
+                    conflict.shared
+                );
+                conflict_count += 1;
+            }
+        }
+        println!("Conflicting pairs: {}", conflict_count);
+        return;
+    }
     let pairs = loader::load_assignment_pairs(&args.filename);
+    if args.coverage {
+        let assignments = pairs
+            .iter()
+            .flat_map(|pair| [pair.0, pair.1])
+            .collect::<Vec<_>>();
+        let covered = IntervalSet::from_sections(assignments.iter());
+        println!(
+            "Covered: {} ({} sections)",
+            format_ranges(covered.ranges()),
+            covered.len()
+        );
+        if let Some(within) = args.within.or(covered.span()) {
+            println!(
+                "Gaps in {}: {}",
+                within,
+                format_ranges(&covered.gaps(&within))
+            );
+        }
+        let busy = interval_set::covered_more_than(&assignments, args.more_than);
+        println!(
+            "Covered by more than {} elves: {} ({} sections)",
+            args.more_than,
+            format_ranges(busy.ranges()),
+            busy.len()
+        );
+        println!(
+            "Redundant work: {} sections",
+            interval_set::redundant_work(&assignments)
+        );
+        return;
+    }
+    if args.relations {
+        for relation in SectionRelation::all() {
+            let count = pairs
+                .iter()
+                .filter(|pair| pair.0.relation(&pair.1) == relation)
+                .count();
+            println!("{}: {}", relation.name(), count);
+        }
+        let shared_sections = pairs
+            .iter()
+            .filter_map(|pair| pair.0.intersection(&pair.1))
+            .map(|shared| shared.len())
+            .sum::<u64>();
+        println!("Sections assigned twice: {}", shared_sections);
+        return;
+    }
     let fully_contained_pairs = pairs
         .iter()
         .filter(|pair| pair.0.overlaps(&pair.1))
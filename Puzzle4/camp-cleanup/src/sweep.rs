@@ -0,0 +1,117 @@
+use super::elf_work::AssignedSections;
+use std::collections::BTreeSet;
+
+// Two elves, by position in the group, and the sections both were given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub first: usize,
+    pub second: usize,
+    pub shared: AssignedSections,
+}
+
+// Walks the assignments by start section keeping the ones still open ordered by end. Whatever
+// is open when an assignment starts overlaps it, so this is O(n log n) plus one step per conflict
+pub fn overlapping_pairs(elves: &[AssignedSections]) -> Vec<Conflict> {
+    let mut by_start = (0..elves.len())
+        .filter(|elf| !elves[*elf].is_empty())
+        .collect::<Vec<_>>();
+    by_start.sort_by_key(|elf| (elves[*elf].start(), *elf));
+    let mut open: BTreeSet<(u32, usize)> = BTreeSet::new();
+    let mut conflicts = vec![];
+    for elf in by_start {
+        let sections = elves[elf];
+        while let Some(&(end, _)) = open.first() {
+            if end >= sections.start() {
+                break;
+            }
+            open.pop_first();
+        }
+        for &(_, other) in open.iter() {
+            conflicts.push(Conflict {
+                first: other.min(elf),
+                second: other.max(elf),
+                shared: sections
+                    .intersection(&elves[other])
+                    .expect("Open assignments overlap the new one"),
+            });
+        }
+        open.insert((sections.end(), elf));
+    }
+    conflicts.sort_by_key(|conflict| (conflict.first, conflict.second));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(elves: &[AssignedSections]) -> Vec<Conflict> {
+        let mut conflicts = vec![];
+        for first in 0..elves.len() {
+            for second in first + 1..elves.len() {
+                if let Some(shared) = elves[first].intersection(&elves[second]) {
+                    conflicts.push(Conflict {
+                        first,
+                        second,
+                        shared,
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn sections(start_index: u32, end_index: u32) -> AssignedSections {
+        AssignedSections::from_bounds(start_index, end_index).unwrap()
+    }
+
+    #[test]
+    fn test_sample_group() {
+        let elves = [
+            sections(2, 8),
+            sections(3, 7),
+            sections(9, 9),
+            sections(8, 9),
+        ];
+        assert_eq!(
+            vec![
+                Conflict {
+                    first: 0,
+                    second: 1,
+                    shared: sections(3, 7)
+                },
+                Conflict {
+                    first: 0,
+                    second: 3,
+                    shared: sections(8, 8)
+                },
+                Conflict {
+                    first: 2,
+                    second: 3,
+                    shared: sections(9, 9)
+                },
+            ],
+            overlapping_pairs(&elves)
+        );
+    }
+    #[test]
+    fn test_matches_brute_force() {
+        // A small linear congruential generator keeps the groups the same on every run
+        let mut seed: u64 = 12345;
+        let mut next = |limit: u32| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as u32 % limit
+        };
+        for group_size in 0..40 {
+            let elves = (0..group_size)
+                .map(|_| {
+                    let start_index = next(30);
+                    sections(start_index, start_index + next(8))
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(brute_force(&elves), overlapping_pairs(&elves));
+        }
+    }
+}